@@ -1,4 +1,4 @@
-use crate::common::BackupType;
+use crate::common::{BackupPhase, BackupType, DumpLocation};
 use serde::Deserialize;
 use std::path::PathBuf;
 
@@ -9,7 +9,27 @@ pub struct AppConfig {
     pub destination_path: PathBuf,
     pub max_number_of_backups: u64,
     pub scale_deployment_enabled: Option<bool>,
+    pub exec_hooks_enabled: Option<bool>,
+    pub docker_quiesce_enabled: Option<bool>,
     pub postgres_backup_enabled: Option<bool>,
     pub mongo_backup_enabled: Option<bool>,
+    pub mysql_backup_enabled: Option<bool>,
+    pub sqlite_backup_enabled: Option<bool>,
+    pub redis_backup_enabled: Option<bool>,
+    pub custom_backup_enabled: Option<bool>,
+    pub http_export_backup_enabled: Option<bool>,
     pub backup_type: Option<BackupType>,
+    /// The phase of every database backup without a phase of its own.
+    pub db_backup_phase: Option<BackupPhase>,
+    pub postgres_backup_phase: Option<BackupPhase>,
+    pub mongo_backup_phase: Option<BackupPhase>,
+    pub mysql_backup_phase: Option<BackupPhase>,
+    pub sqlite_backup_phase: Option<BackupPhase>,
+    pub redis_backup_phase: Option<BackupPhase>,
+    /// The number of database backups within a phase that run at the same time.
+    pub db_backup_concurrency: Option<usize>,
+    pub file_backup_phase: Option<BackupPhase>,
+    pub db_dump_location: Option<DumpLocation>,
+    /// The directory database backups are staged in when `db_dump_location` is `STAGING`.
+    pub db_staging_path: Option<PathBuf>,
 }
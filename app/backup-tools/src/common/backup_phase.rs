@@ -0,0 +1,40 @@
+use serde::Deserialize;
+
+/// When a backup step runs relative to the window in which the workload is scaled down and its containers are
+/// quiesced. Exec hooks surround all phases.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BackupPhase {
+    /// While the workload is still running, for dumps that are consistent without stopping it.
+    Before,
+    /// While the workload is scaled down.
+    #[default]
+    During,
+    /// After the workload was scaled back up.
+    After,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BackupPhase;
+    use serde::de::IntoDeserializer;
+    use serde::Deserialize;
+
+    fn deserialize(s: &str) -> Result<BackupPhase, serde::de::value::Error> {
+        BackupPhase::deserialize(s.into_deserializer())
+    }
+
+    #[test]
+    fn deserialize_phases() {
+        assert_eq!(deserialize("BEFORE").unwrap(), BackupPhase::Before);
+        assert_eq!(deserialize("DURING").unwrap(), BackupPhase::During);
+        assert_eq!(deserialize("AFTER").unwrap(), BackupPhase::After);
+        assert!(deserialize("during").is_err());
+    }
+
+    #[test]
+    fn phases_are_ordered() {
+        assert!(BackupPhase::Before < BackupPhase::During);
+        assert!(BackupPhase::During < BackupPhase::After);
+    }
+}
@@ -0,0 +1,87 @@
+use envy::prefixed;
+use serde::Deserialize;
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::path::Path;
+use tracing::warn;
+
+pub const CHILD_ENV_PREFIX: &str = "CHILD_ENV_";
+/// Variables every child process receives, where a trailing `*` matches any suffix.
+const DEFAULT_PASSTHROUGH: [&str; 5] = ["PATH", "HOME", "TZ", "LANG", "LC_*"];
+
+/// Variables passed on to child processes, either to every one (`CHILD_ENV_PASSTHROUGH`) or to a single tool
+/// (`CHILD_ENV_<TOOL>_PASSTHROUGH`).
+#[derive(Debug, Default, Deserialize)]
+struct ChildEnvConfig {
+    passthrough: Option<Vec<String>>,
+}
+
+/// Selects the variables of backup-tools' environment that `program` is started with. Everything else, including
+/// the credentials of other databases, is withheld; the variables a tool needs to connect are set on its command.
+pub fn child_env(program: &OsStr) -> Vec<(OsString, OsString)> {
+    let mut patterns: Vec<String> = DEFAULT_PASSTHROUGH
+        .iter()
+        .map(|p| String::from(*p))
+        .collect();
+    for prefix in [String::from(CHILD_ENV_PREFIX), tool_prefix(program)] {
+        let config = prefixed(&prefix)
+            .from_env::<ChildEnvConfig>()
+            .unwrap_or_else(|ex| {
+                warn!(ex=?ex, prefix, "Failed to read child process environment config.");
+                ChildEnvConfig::default()
+            });
+        patterns.extend(config.passthrough.unwrap_or_default());
+    }
+
+    env::vars_os()
+        .filter(|(key, _)| key.to_str().is_some_and(|key| is_allowed(key, &patterns)))
+        .collect()
+}
+
+/// Builds the prefix of a tool's settings from the file name of its program, e.g. `CHILD_ENV_PG_DUMP_`.
+fn tool_prefix(program: &OsStr) -> String {
+    let name = Path::new(program)
+        .file_name()
+        .unwrap_or(program)
+        .to_string_lossy();
+
+    format!(
+        "{}{}_",
+        CHILD_ENV_PREFIX,
+        name.replace(['-', '.'], "_").to_uppercase()
+    )
+}
+
+fn is_allowed(key: &str, patterns: &[String]) -> bool {
+    patterns
+        .iter()
+        .any(|pattern| match pattern.strip_suffix('*') {
+            Some(prefix) => key.starts_with(prefix),
+            None => key == pattern,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_allowed, tool_prefix};
+    use std::ffi::OsStr;
+
+    #[test]
+    fn tool_prefix_uses_file_name_of_program() {
+        assert_eq!(
+            tool_prefix(OsStr::new("/usr/lib/postgresql/16/bin/pg_dump")),
+            "CHILD_ENV_PG_DUMP_"
+        );
+        assert_eq!(tool_prefix(OsStr::new("redis-cli")), "CHILD_ENV_REDIS_CLI_");
+    }
+
+    #[test]
+    fn is_allowed_matches_names_and_prefixes() {
+        let patterns = vec![String::from("PATH"), String::from("LC_*")];
+
+        assert!(is_allowed("PATH", &patterns));
+        assert!(is_allowed("LC_ALL", &patterns));
+        assert!(!is_allowed("PATHEXT", &patterns));
+        assert!(!is_allowed("POSTGRES_PASSWORD", &patterns));
+    }
+}
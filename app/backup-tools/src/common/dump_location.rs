@@ -0,0 +1,33 @@
+use serde::Deserialize;
+
+/// Where database backups are written before the file backup picks them up.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DumpLocation {
+    /// The `db` directory of the source path, which must be writable.
+    #[default]
+    Source,
+    /// The `db` directory of a staging path, which is wiped at the start of each run and merged into the backup.
+    Staging,
+    /// The `db` directory of the new backup itself, for incremental backups only.
+    Backup,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DumpLocation;
+    use serde::de::IntoDeserializer;
+    use serde::Deserialize;
+
+    fn deserialize(s: &str) -> Result<DumpLocation, serde::de::value::Error> {
+        DumpLocation::deserialize(s.into_deserializer())
+    }
+
+    #[test]
+    fn deserialize_locations() {
+        assert_eq!(deserialize("SOURCE").unwrap(), DumpLocation::Source);
+        assert_eq!(deserialize("STAGING").unwrap(), DumpLocation::Staging);
+        assert_eq!(deserialize("BACKUP").unwrap(), DumpLocation::Backup);
+        assert!(deserialize("staging").is_err());
+    }
+}
@@ -1,4 +1,15 @@
+mod backup_phase;
 mod backup_type;
+mod child_env;
+mod dump_location;
+pub mod output;
+pub mod private_file;
 pub mod process;
+pub mod redact;
+pub mod retry;
+pub mod secret_files;
+pub mod tls;
 
+pub use backup_phase::BackupPhase;
 pub use backup_type::BackupType;
+pub use dump_location::DumpLocation;
@@ -0,0 +1,184 @@
+use anyhow::{anyhow, Context, Result};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::File;
+use std::io::{copy, BufWriter, Read, Write};
+use std::path::Path;
+use std::process::Child;
+use std::thread::JoinHandle;
+use tracing::{debug, warn};
+
+/// Streams the stdout of a child process into a file, optionally gzip compressed, on a separate thread.
+///
+/// The child's stdout is taken so that `wait_for_child` only logs stderr. The returned handle must be joined after
+/// waiting for the child to find out whether the output was written completely; it yields the number of bytes read
+/// from the child. If capturing cannot be started, the child is killed.
+pub fn capture_stdout(
+    child: &mut Child,
+    path: &Path,
+    compress: bool,
+) -> Result<JoinHandle<Result<u64>>> {
+    start_capture(child, path, compress).inspect_err(|_| kill_child(child))
+}
+
+fn start_capture(
+    child: &mut Child,
+    path: &Path,
+    compress: bool,
+) -> Result<JoinHandle<Result<u64>>> {
+    let mut stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| anyhow!("Child process stdout was not piped."))?;
+    let file = File::create(path)
+        .with_context(|| format!("Failed to create output file {}.", path.display()))?;
+    let display_path = path.display().to_string();
+
+    std::thread::Builder::new()
+        .name(String::from("stdout_capture"))
+        .spawn(move || {
+            let writer = BufWriter::new(file);
+            let bytes = if compress {
+                let mut encoder = GzEncoder::new(writer, Compression::default());
+                let bytes = copy(&mut stdout, &mut encoder)?;
+                encoder.finish()?.flush()?;
+                bytes
+            } else {
+                let mut writer = writer;
+                let bytes = copy(&mut stdout, &mut writer)?;
+                writer.flush()?;
+                bytes
+            };
+
+            debug!(bytes, path=%display_path, "Finished writing process output to file.");
+            Ok(bytes)
+        })
+        .context("Failed to start stdout capture thread.")
+}
+
+/// Reads the stdout of a child process into a string on a separate thread, for commands whose output is inspected
+/// rather than logged. The returned handle must be joined after waiting for the child.
+pub fn read_stdout(child: &mut Child) -> Result<JoinHandle<Result<String>>> {
+    let mut stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| anyhow!("Child process stdout was not piped."))?;
+
+    std::thread::Builder::new()
+        .name(String::from("stdout_capture"))
+        .spawn(move || {
+            let mut output = String::new();
+            stdout.read_to_string(&mut output)?;
+            Ok(output)
+        })
+        .context("Failed to start stdout capture thread.")
+}
+
+/// Kills and reaps a child whose output can no longer be handled, so that it neither keeps running nor lingers as a
+/// zombie.
+fn kill_child(child: &mut Child) {
+    child
+        .kill()
+        .unwrap_or_else(|ex| warn!(ex=?ex, "Failed to kill child process."));
+    child
+        .wait()
+        .map(|_| ())
+        .unwrap_or_else(|ex| warn!(ex=?ex, "Failed to wait for killed child process."));
+}
+
+/// Joins a thread started by `capture_stdout` or `read_stdout`.
+pub fn join_capture<T>(handle: JoinHandle<Result<T>>) -> Result<T> {
+    handle
+        .join()
+        .map_err(|_| anyhow!("Stdout capture thread panicked."))?
+        .context("Error while capturing process output.")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{capture_stdout, join_capture, read_stdout};
+    use flate2::read::GzDecoder;
+    use std::env::temp_dir;
+    use std::fs;
+    use std::io::Read;
+    use std::process::{Command, Stdio};
+
+    #[test]
+    fn capture_stdout_given_compress_writes_gzip() {
+        let path = temp_dir().join("backup_tools_capture_stdout_compressed.gz");
+        let mut child = Command::new("echo")
+            .arg("hello")
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let handle = capture_stdout(&mut child, &path, true).unwrap();
+        child.wait().unwrap();
+        let bytes = join_capture(handle).unwrap();
+
+        let mut contents = String::new();
+        GzDecoder::new(fs::File::open(&path).unwrap())
+            .read_to_string(&mut contents)
+            .unwrap();
+        fs::remove_file(&path).ok();
+        assert_eq!(bytes, 6);
+        assert_eq!(contents, "hello\n");
+    }
+
+    #[test]
+    fn capture_stdout_given_no_compress_writes_plain() {
+        let path = temp_dir().join("backup_tools_capture_stdout_plain.txt");
+        let mut child = Command::new("echo")
+            .arg("hello")
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let handle = capture_stdout(&mut child, &path, false).unwrap();
+        child.wait().unwrap();
+        join_capture(handle).unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).ok();
+        assert_eq!(contents, "hello\n");
+    }
+
+    #[test]
+    fn read_stdout_returns_output() {
+        let mut child = Command::new("echo")
+            .arg("ok")
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let handle = read_stdout(&mut child).unwrap();
+        child.wait().unwrap();
+
+        assert_eq!(join_capture(handle).unwrap(), "ok\n");
+    }
+
+    #[test]
+    fn capture_stdout_given_stdout_not_piped_returns_error() {
+        let path = temp_dir().join("backup_tools_capture_stdout_not_piped.txt");
+        let mut child = Command::new("true").stdout(Stdio::null()).spawn().unwrap();
+
+        let result = capture_stdout(&mut child, &path, false);
+        child.wait().unwrap();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn capture_stdout_given_unwritable_path_kills_child() {
+        let path = temp_dir().join("backup_tools_missing_dir/capture.txt");
+        let mut child = Command::new("sleep")
+            .arg("30")
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let result = capture_stdout(&mut child, &path, false);
+
+        assert!(result.is_err());
+        assert!(child.try_wait().unwrap().is_some());
+    }
+}
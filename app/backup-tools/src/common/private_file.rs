@@ -0,0 +1,77 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use tracing::warn;
+
+static NEXT_ID: AtomicU32 = AtomicU32::new(0);
+
+/// A temporary file readable only by the current user, used to hand secrets to child processes without exposing them
+/// in the process list. The file is removed when dropped.
+#[derive(Debug)]
+pub struct PrivateFile {
+    path: PathBuf,
+}
+
+impl PrivateFile {
+    pub fn create(name: &str, contents: &str) -> Result<PrivateFile> {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let path = std::env::temp_dir().join(format!(
+            "backup-tools-{}-{}-{}",
+            std::process::id(),
+            id,
+            name
+        ));
+
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&path)
+            .with_context(|| format!("Failed to create private file {}.", path.display()))?;
+        let private_file = PrivateFile { path };
+
+        file.write_all(contents.as_bytes())
+            .and_then(|_| file.flush())
+            .context("Failed to write private file.")?;
+
+        Ok(private_file)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for PrivateFile {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_file(&self.path) {
+            warn!(ex=?e, path=%self.path.display(), "Failed to remove private file.");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PrivateFile;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn create_writes_owner_only_file_and_drop_removes_it() {
+        let file = PrivateFile::create("test.yaml", "password: secret\n").unwrap();
+        let path = file.path().to_path_buf();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "password: secret\n");
+        assert_eq!(
+            fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+
+        drop(file);
+        assert!(!path.exists());
+    }
+}
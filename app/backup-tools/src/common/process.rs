@@ -1,5 +1,9 @@
+use std::collections::VecDeque;
+use std::ffi::OsStr;
+use std::fmt;
 use std::io;
-use anyhow::{anyhow, bail, Context, Result};
+use anyhow::{anyhow, Context, Result};
+use crate::common::child_env::child_env;
 use crossbeam::channel::{after, never, Receiver};
 use crossbeam::select;
 use nix::libc::pid_t;
@@ -8,17 +12,54 @@ use nix::unistd::Pid;
 use std::io::{BufRead, BufReader};
 use std::os::unix::process::ExitStatusExt;
 use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
 use std::thread::JoinHandle;
 use std::time::{Duration, Instant};
 use tracing::{debug, error, error_span, info, info_span, Span, warn};
 
 const WAIT_DURATION_SECS: u64 = 5;
 const DEFAULT_TIMEOUT_SECS: u64 = (60 * 2) + 30; // Two minutes and thirty seconds
+/// How many of the last lines written to stderr are kept to describe a failed process.
+const STDERR_TAIL_LINES: usize = 20;
 
-pub fn create_command(program: &str) -> Command {
-    let mut command = Command::new(program);
+/// Why waiting for a child process failed, so that callers can decide whether running it again may help.
+#[derive(Debug)]
+pub enum ProcessError {
+    Shutdown,
+    TimedOut,
+    Failed {
+        status: ExitStatus,
+        /// The last lines the process wrote to stderr.
+        stderr: Vec<String>,
+    },
+}
+
+impl fmt::Display for ProcessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProcessError::Shutdown => write!(f, "Killed process due to shutdown."),
+            ProcessError::TimedOut => write!(f, "Killed process due to timeout."),
+            ProcessError::Failed { status, .. } => write!(f, "Process exited unsuccessfully: {}", status),
+        }
+    }
+}
+
+impl std::error::Error for ProcessError {}
+
+/// Whether the error, or any error it wraps, is a process that was killed due to a shutdown.
+pub fn is_shutdown(error: &anyhow::Error) -> bool {
+    error
+        .chain()
+        .any(|e| matches!(e.downcast_ref::<ProcessError>(), Some(ProcessError::Shutdown)))
+}
+
+/// Creates a command that starts from a cleared environment, receiving only the variables allowed by `child_env`.
+pub fn create_command(program: impl AsRef<OsStr>) -> Command {
+    let mut command = Command::new(&program);
 
     command
+        .env_clear()
+        .envs(child_env(program.as_ref()))
         .stdin(Stdio::null())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
@@ -35,12 +76,61 @@ pub fn wait_for_child(
 }
 
 pub fn wait_for_child_with_redirection(
-    mut child: Child,
+    child: Child,
+    timeout: Option<Duration>,
+    shutdown_rx: &Receiver<()>,
+    stderr_as_stdout: bool,
+) -> Result<()> {
+    wait_for_exit(child, timeout, shutdown_rx, stderr_as_stdout).map(|_| ())
+}
+
+/// Like `wait_for_child`, but returns an error if the process exits with a non-success exit code.
+pub fn wait_for_child_success(
+    child: Child,
+    timeout: Option<Duration>,
+    shutdown_rx: &Receiver<()>,
+) -> Result<()> {
+    wait_for_child_success_with_redirection(child, timeout, shutdown_rx, false)
+}
+
+/// Like `wait_for_child_with_redirection`, but returns an error if the process exits with a non-success exit code.
+pub fn wait_for_child_success_with_redirection(
+    child: Child,
     timeout: Option<Duration>,
     shutdown_rx: &Receiver<()>,
     stderr_as_stdout: bool,
 ) -> Result<()> {
+    let (status, stderr) = wait_for_exit(child, timeout, shutdown_rx, stderr_as_stdout)?;
+    if !status.success() {
+        return Err(ProcessError::Failed { status, stderr }.into());
+    }
+
+    Ok(())
+}
+
+/// Like `wait_for_child_success`, but treats any of the given exit codes as success.
+pub fn wait_for_child_exit_codes(
+    child: Child,
+    timeout: Option<Duration>,
+    shutdown_rx: &Receiver<()>,
+    success_codes: &[i32],
+) -> Result<()> {
+    let (status, stderr) = wait_for_exit(child, timeout, shutdown_rx, false)?;
+    if !status.code().is_some_and(|code| success_codes.contains(&code)) {
+        return Err(ProcessError::Failed { status, stderr }.into());
+    }
+
+    Ok(())
+}
+
+fn wait_for_exit(
+    mut child: Child,
+    timeout: Option<Duration>,
+    shutdown_rx: &Receiver<()>,
+    stderr_as_stdout: bool,
+) -> Result<(ExitStatus, Vec<String>)> {
     let timeout = timeout.unwrap_or_else(|| Duration::from_secs(DEFAULT_TIMEOUT_SECS));
+    let stderr_tail = Arc::new(Mutex::new(VecDeque::with_capacity(STDERR_TAIL_LINES)));
     let sleep_duration = Some(Duration::from_secs(WAIT_DURATION_SECS));
     let start = Instant::now();
 
@@ -56,20 +146,33 @@ pub fn wait_for_child_with_redirection(
         .stderr
         .take()
         .and_then(|s| {
+            let tail = Arc::clone(&stderr_tail);
+            let keep_line = move |line: &str| {
+                let mut tail = tail.lock().expect("stderr tail lock was poisoned.");
+                if tail.len() == STDERR_TAIL_LINES {
+                    tail.pop_front();
+                }
+                tail.push_back(String::from(line));
+            };
             let stream_handle = if stderr_as_stdout {
-                read_stream(info_span!("stdout"), s, Box::new(|line| info!("{}", line)))
+                read_stream(info_span!("stdout"), s, Box::new(move |line| {
+                    keep_line(&line);
+                    info!("{}", line)
+                }))
             } else {
-                read_stream(error_span!("stderr"), s, Box::new(|line| error!("{}", line)))
+                read_stream(error_span!("stderr"), s, Box::new(move |line| {
+                    keep_line(&line);
+                    error!("{}", line)
+                }))
             };
             stream_handle
                 .inspect_err(|e| error!(ex=?e, "Failed to open stderr stream."))
                 .ok()
         });
 
-    loop {
+    let exit_status = loop {
         let wait_result = child
             .try_wait()
-            .map(handle_exit_status)
             .map_err(|e| {
                 child
                     .kill()
@@ -84,8 +187,9 @@ pub fn wait_for_child_with_redirection(
 
         let wait_option = wait_result.expect("Did not check wait_result for errors.");
 
-        if wait_option.is_some() {
-            break;
+        if let Some(exit_status) = wait_option {
+            handle_exit_status(Some(exit_status));
+            break exit_status;
         }
 
         let sleep = sleep_duration.map(after).unwrap_or(never());
@@ -95,7 +199,7 @@ pub fn wait_for_child_with_redirection(
                 kill(Pid::from_raw(child.id() as pid_t), Signal::SIGTERM)?;
                 let exit_status = child.wait().context("Failed to retrieve exit status after sending SIGTERM to child.")?;
                 handle_exit_status(Some(exit_status)).unwrap_or(());
-                bail!("Killed process due to shutdown.");
+                return Err(ProcessError::Shutdown.into());
             },
             recv(sleep) -> _ => {
                 if start.elapsed() <= timeout {
@@ -113,11 +217,11 @@ pub fn wait_for_child_with_redirection(
                     child.kill().context("Failed to send SIGKILL to child.")?;
                     let exit_status = child.wait().context("Failed to retrieve exit status after sending SIGKILL to child.")?;
                     handle_exit_status(Some(exit_status)).unwrap_or(());
-                    bail!("Killed process due to timeout.");
+                    return Err(ProcessError::TimedOut.into());
                 }
             }
         }
-    }
+    };
 
     if let Some(handle) = stdout_thread {
         handle.join().expect("Could not join to stdout thread.");
@@ -127,7 +231,13 @@ pub fn wait_for_child_with_redirection(
         handle.join().expect("Could not join to stderr thread.");
     }
 
-    Ok(())
+    let stderr = stderr_tail
+        .lock()
+        .expect("stderr tail lock was poisoned.")
+        .drain(..)
+        .collect();
+
+    Ok((exit_status, stderr))
 }
 
 fn handle_exit_status(
@@ -0,0 +1,174 @@
+use crate::common::secret_files::is_secret;
+use percent_encoding::percent_decode_str;
+use std::borrow::Cow;
+use std::env;
+use std::ffi::OsStr;
+use std::fmt;
+use std::io::{self, Stdout, Write};
+use std::process::Command;
+use std::sync::RwLock;
+use tracing_subscriber::fmt::MakeWriter;
+
+const MASK: &str = "********";
+
+/// Every known secret value, longest first so that a secret containing another is masked completely.
+static SECRETS: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// Masks `value` wherever it appears in log output from now on.
+pub fn register_secret(value: &str) {
+    if value.is_empty() {
+        return;
+    }
+
+    let mut secrets = SECRETS.write().expect("Secret registry was poisoned.");
+    // Debug output, e.g. of a command's environment, escapes quotes and control characters.
+    for form in [String::from(value), value.escape_debug().to_string()] {
+        if !secrets.contains(&form) {
+            secrets.push(form);
+        }
+    }
+    secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+}
+
+/// Registers the values of every secret setting in the environment, and the passwords of URLs with credentials.
+///
+/// Must be called once the environment is complete, after secret files and annotations were applied.
+pub fn register_env_secrets() {
+    for (key, value) in env::vars_os() {
+        let (Some(key), Some(value)) = (key.to_str(), value.to_str()) else {
+            continue;
+        };
+        if !is_secret(key) {
+            continue;
+        }
+
+        if key.ends_with("URL") {
+            if let Some(password) = url_password(value) {
+                register_secret(password);
+                register_secret(&percent_decode_str(password).decode_utf8_lossy());
+            }
+        } else {
+            register_secret(value);
+        }
+    }
+}
+
+/// Replaces every known secret value in `text`.
+pub fn redact(text: &str) -> Cow<'_, str> {
+    let secrets = SECRETS.read().expect("Secret registry was poisoned.");
+    let mut text = Cow::Borrowed(text);
+    for secret in secrets.iter() {
+        if text.contains(secret.as_str()) {
+            text = Cow::Owned(text.replace(secret.as_str(), MASK));
+        }
+    }
+
+    text
+}
+
+fn url_password(url: &str) -> Option<&str> {
+    let (_, rest) = url.split_once("://")?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let (userinfo, _) = authority.rsplit_once('@')?;
+    let (_, password) = userinfo.split_once(':')?;
+
+    Some(password)
+}
+
+/// Records which environment variables of a command hold secrets.
+pub trait SecretCommandExt {
+    /// Sets an environment variable of the command whose value is masked in log output.
+    fn secret_env(&mut self, key: impl AsRef<OsStr>, value: &str) -> &mut Command;
+}
+
+impl SecretCommandExt for Command {
+    fn secret_env(&mut self, key: impl AsRef<OsStr>, value: &str) -> &mut Command {
+        register_secret(value);
+        self.env(key, value)
+    }
+}
+
+/// Formats a command for debug logging with its secrets masked.
+pub struct RedactedCommand<'a>(&'a Command);
+
+pub fn redacted(command: &Command) -> RedactedCommand<'_> {
+    RedactedCommand(command)
+}
+
+impl fmt::Debug for RedactedCommand<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&redact(&format!("{:?}", self.0)))
+    }
+}
+
+/// Writes formatted log events to stdout with every known secret value masked, including those in the output of
+/// child processes that is logged line by line.
+pub struct RedactingWriter;
+
+impl<'a> MakeWriter<'a> for RedactingWriter {
+    type Writer = RedactingStdout;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingStdout(io::stdout())
+    }
+}
+
+pub struct RedactingStdout(Stdout);
+
+impl Write for RedactingStdout {
+    /// Expects a complete event, as the formatter writes each one at once.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let text = String::from_utf8_lossy(buf);
+        self.0.write_all(redact(&text).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{redact, redacted, register_secret, url_password, SecretCommandExt};
+    use std::process::Command;
+
+    #[test]
+    fn url_password_reads_userinfo() {
+        assert_eq!(
+            url_password("postgresql://user:p%40ss@db:5432/app"),
+            Some("p%40ss")
+        );
+        assert_eq!(
+            url_password("mongodb://user:secret@a:27017,b:27017/?replicaSet=rs0"),
+            Some("secret")
+        );
+        assert_eq!(url_password("postgresql://user@db/app"), None);
+        assert_eq!(url_password("https://gitea/dump"), None);
+    }
+
+    #[test]
+    fn redact_masks_registered_secrets() {
+        register_secret("hunter2-redact");
+        register_secret("");
+
+        assert_eq!(
+            redact("password=hunter2-redact host=db"),
+            "password=******** host=db"
+        );
+        assert_eq!(redact("nothing to hide"), "nothing to hide");
+    }
+
+    #[test]
+    fn redacted_masks_secret_env_of_command() {
+        let mut command = Command::new("pg_dump");
+        command
+            .env("PGUSER", "postgres")
+            .secret_env("PGPASSWORD", "s\"cret-command");
+
+        let debug = format!("{:?}", redacted(&command));
+
+        assert!(debug.contains("postgres"));
+        assert!(!debug.contains("cret-command"));
+    }
+}
@@ -0,0 +1,213 @@
+use crate::common::process::ProcessError;
+use anyhow::Result;
+use crossbeam::channel::{after, Receiver};
+use crossbeam::select;
+use serde::Deserialize;
+use std::time::Duration;
+use tracing::{info, warn};
+
+const DEFAULT_ATTEMPTS: u32 = 1;
+const DEFAULT_BACKOFF_SECS: u64 = 30;
+/// Messages written by the database clients when the server could not be reached, matched case insensitively.
+const CONNECTION_ERRORS: [&str; 9] = [
+    "connection refused",
+    "connection reset",
+    "could not connect",
+    "could not translate host name",
+    "temporary failure in name resolution",
+    "server closed the connection unexpectedly",
+    "no reachable servers",
+    "server selection error",
+    "can't connect",
+];
+
+/// Which failures of a process are worth retrying.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum RetryOn {
+    /// The process could not connect to the server or lost its connection.
+    Connection,
+    /// The process was killed after reaching its timeout.
+    Timeout,
+    /// The process exited unsuccessfully for any reason.
+    Any,
+}
+
+/// How many times an operation is attempted and how long to wait between attempts.
+///
+/// The delay starts at the backoff and doubles after every failed attempt. Operations are never retried after a
+/// shutdown was requested.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    attempts: u32,
+    backoff: Duration,
+    retry_on: Vec<RetryOn>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::new(None, None, None)
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(
+        attempts: Option<u32>,
+        backoff_secs: Option<u64>,
+        retry_on: Option<&[RetryOn]>,
+    ) -> Self {
+        RetryPolicy {
+            attempts: attempts.unwrap_or(DEFAULT_ATTEMPTS).max(1),
+            backoff: Duration::from_secs(backoff_secs.unwrap_or(DEFAULT_BACKOFF_SECS)),
+            retry_on: retry_on
+                .filter(|r| !r.is_empty())
+                .map_or_else(|| vec![RetryOn::Connection], <[RetryOn]>::to_vec),
+        }
+    }
+
+    /// Runs the operation until it succeeds, fails with an error that is not retryable, or runs out of attempts.
+    pub fn run<T>(
+        &self,
+        shutdown_rx: &Receiver<()>,
+        mut operation: impl FnMut() -> Result<T>,
+    ) -> Result<T> {
+        let mut attempt = 1;
+        loop {
+            let error = match operation() {
+                Ok(value) => return Ok(value),
+                Err(e) => e,
+            };
+
+            if attempt >= self.attempts || !self.is_retryable(&error) {
+                return Err(error);
+            }
+
+            let delay = self.delay(attempt);
+            warn!(
+                ex=?error,
+                attempt,
+                attempts = self.attempts,
+                delay_seconds = delay.as_secs(),
+                "Attempt failed, retrying after a delay."
+            );
+            select! {
+                recv(shutdown_rx) -> _ => {
+                    warn!("Received notification to shutdown, no longer retrying.");
+                    return Err(anyhow::Error::new(ProcessError::Shutdown)
+                        .context("Stopped retrying due to shutdown."));
+                },
+                recv(after(delay)) -> _ => {}
+            }
+
+            attempt += 1;
+            info!(attempt, "Retrying.");
+        }
+    }
+
+    fn delay(&self, attempt: u32) -> Duration {
+        self.backoff
+            .saturating_mul(2u32.saturating_pow(attempt - 1))
+    }
+
+    fn is_retryable(&self, error: &anyhow::Error) -> bool {
+        let Some(process_error) = error.chain().find_map(|e| e.downcast_ref::<ProcessError>())
+        else {
+            return false;
+        };
+
+        match process_error {
+            ProcessError::Shutdown => false,
+            ProcessError::TimedOut => self
+                .retry_on
+                .iter()
+                .any(|r| matches!(r, RetryOn::Timeout | RetryOn::Any)),
+            ProcessError::Failed { stderr, .. } => self.retry_on.iter().any(|r| match r {
+                RetryOn::Any => true,
+                RetryOn::Connection => is_connection_error(stderr),
+                RetryOn::Timeout => false,
+            }),
+        }
+    }
+}
+
+fn is_connection_error(stderr: &[String]) -> bool {
+    stderr.iter().any(|line| {
+        let line = line.to_lowercase();
+        CONNECTION_ERRORS
+            .iter()
+            .any(|message| line.contains(message))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RetryOn, RetryPolicy};
+    use crate::common::process::{is_shutdown, ProcessError};
+    use anyhow::anyhow;
+    use crossbeam::channel::unbounded;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::ExitStatus;
+    use std::time::Duration;
+
+    fn failed(stderr: &str) -> anyhow::Error {
+        anyhow::Error::new(ProcessError::Failed {
+            status: ExitStatus::from_raw(1 << 8),
+            stderr: vec![String::from(stderr)],
+        })
+    }
+
+    #[test]
+    fn is_retryable_given_connection_refused_retries_by_default() {
+        let policy = RetryPolicy::default();
+
+        let error = failed("pg_dump: error: connection to server failed: Connection refused")
+            .context("Failed to back up database.");
+        assert!(policy.is_retryable(&error));
+        assert!(!policy.is_retryable(&failed("pg_dump: error: permission denied for table users")));
+        assert!(!policy.is_retryable(&anyhow::Error::new(ProcessError::TimedOut)));
+        assert!(!policy.is_retryable(&anyhow!("No such file or directory")));
+    }
+
+    #[test]
+    fn is_retryable_never_retries_shutdown() {
+        let policy = RetryPolicy::new(None, None, Some(&[RetryOn::Any]));
+
+        assert!(policy.is_retryable(&failed("permission denied")));
+        assert!(policy.is_retryable(&anyhow::Error::new(ProcessError::TimedOut)));
+        assert!(!policy.is_retryable(&anyhow::Error::new(ProcessError::Shutdown)));
+    }
+
+    #[test]
+    fn delay_doubles_after_each_attempt() {
+        let policy = RetryPolicy::new(Some(4), Some(10), None);
+
+        assert_eq!(policy.delay(1), Duration::from_secs(10));
+        assert_eq!(policy.delay(3), Duration::from_secs(40));
+    }
+
+    #[test]
+    fn run_stops_after_configured_attempts() {
+        let (_tx, rx) = unbounded();
+        let policy = RetryPolicy::new(Some(3), Some(0), Some(&[RetryOn::Any]));
+        let mut calls = 0;
+
+        let result: anyhow::Result<()> = policy.run(&rx, || {
+            calls += 1;
+            Err(failed("boom"))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn run_given_shutdown_during_delay_returns_shutdown_error() {
+        let (tx, rx) = unbounded();
+        let policy = RetryPolicy::new(Some(2), Some(60), Some(&[RetryOn::Any]));
+        tx.send(()).unwrap();
+
+        let result: anyhow::Result<()> = policy.run(&rx, || Err(failed("boom")));
+
+        assert!(is_shutdown(&result.unwrap_err()));
+    }
+}
@@ -0,0 +1,121 @@
+use anyhow::{bail, Context, Result};
+use std::collections::BTreeMap;
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::path::PathBuf;
+use tracing::info;
+
+const FILE_SUFFIX: &str = "_FILE";
+/// The final segments of the names of settings that hold secrets and may therefore be read from a file.
+const SECRET_NAMES: [&str; 3] = ["PASSWORD", "URL", "TOKEN"];
+
+/// Reads every secret setting given as `<NAME>_FILE`, e.g. `POSTGRES_PASSWORD_FILE`, from the named file and exports
+/// its trimmed contents as `<NAME>`, so that secrets mounted as files never have to appear in the environment of the
+/// container's spec. Setting both `<NAME>` and `<NAME>_FILE` is an error.
+///
+/// Must be called before any other threads are started as it modifies the process's environment.
+pub fn apply_secret_files() -> Result<()> {
+    let vars: BTreeMap<String, OsString> = env::vars_os()
+        .filter_map(|(key, value)| Some((key.into_string().ok()?, value)))
+        .collect();
+
+    for (key, path) in secret_files(&vars)? {
+        let value = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {} from {}.", key, path.display()))?;
+
+        info!(key=%key, path=%path.display(), "Setting environment variable from secret file.");
+        // SAFETY: Called during startup before any other threads exist.
+        unsafe { env::set_var(&key, value.trim()) };
+    }
+
+    Ok(())
+}
+
+/// Pairs the names of secret settings with the files they are read from.
+fn secret_files(vars: &BTreeMap<String, OsString>) -> Result<Vec<(String, PathBuf)>> {
+    let mut files = Vec::new();
+    for (key, value) in vars {
+        let Some(name) = key.strip_suffix(FILE_SUFFIX) else {
+            continue;
+        };
+        if !is_secret(name) {
+            continue;
+        }
+        if vars.contains_key(name) {
+            bail!("Only one of {} and {} may be set.", name, key);
+        }
+
+        files.push((String::from(name), PathBuf::from(value)));
+    }
+
+    Ok(files)
+}
+
+/// Whether a setting holds a secret, judged by the final segment of its name.
+pub fn is_secret(name: &str) -> bool {
+    SECRET_NAMES.iter().any(|secret| {
+        name == *secret
+            || name
+                .strip_suffix(secret)
+                .is_some_and(|prefix| prefix.ends_with('_'))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_secret, secret_files};
+    use std::collections::BTreeMap;
+    use std::ffi::OsString;
+    use std::path::PathBuf;
+
+    fn vars(pairs: &[(&str, &str)]) -> BTreeMap<String, OsString> {
+        pairs
+            .iter()
+            .map(|(key, value)| (String::from(*key), OsString::from(value)))
+            .collect()
+    }
+
+    #[test]
+    fn is_secret_matches_final_name_segment() {
+        assert!(is_secret("POSTGRES_PASSWORD"));
+        assert!(is_secret("POSTGRES_APP_DB_URL"));
+        assert!(is_secret("MONGO_TLS_CERTIFICATE_KEY_FILE_PASSWORD"));
+        assert!(!is_secret("MONGO_CONFIGURATION"));
+        assert!(!is_secret("MONGO_TLS_CA"));
+        assert!(!is_secret("CUSTOM_CURL"));
+    }
+
+    #[test]
+    fn secret_files_ignores_other_file_settings() {
+        let vars = vars(&[
+            ("POSTGRES_PASSWORD_FILE", "/secrets/password"),
+            ("MONGO_QUERY_FILE", "/config/query.json"),
+            ("HTTP_EXPORT_GITEA_TOKEN_FILE", "/secrets/token"),
+        ]);
+
+        assert_eq!(
+            secret_files(&vars).unwrap(),
+            vec![
+                (
+                    String::from("HTTP_EXPORT_GITEA_TOKEN"),
+                    PathBuf::from("/secrets/token")
+                ),
+                (
+                    String::from("POSTGRES_PASSWORD"),
+                    PathBuf::from("/secrets/password")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn secret_files_given_both_forms_returns_error() {
+        let vars = vars(&[
+            ("MYSQL_PASSWORD", "pass"),
+            ("MYSQL_PASSWORD_FILE", "/secrets/password"),
+        ]);
+
+        assert!(secret_files(&vars).is_err());
+    }
+}
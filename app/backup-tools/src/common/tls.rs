@@ -0,0 +1,37 @@
+use anyhow::{Context, Result};
+use rustls_native_certs::load_native_certs;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use tracing::warn;
+use ureq::tls::Certificate;
+
+/// Loads the certificates from the native store, along with those in the PEM file at `ca_path` if given.
+pub fn load_certs(ca_path: Option<&Path>) -> Result<Vec<Certificate<'static>>> {
+    let mut certs: Vec<Certificate<'static>> = Vec::new();
+
+    // Add certs from native store.
+    let cert_result = load_native_certs();
+
+    cert_result
+        .errors
+        .iter()
+        .for_each(|error| warn!(ex=?error, "Encountered an error while loading a certificate from the native store."));
+
+    for native_cert in cert_result.certs {
+        certs.push(Certificate::from_der(native_cert.as_ref()).to_owned());
+    }
+
+    // Load additional CA certs.
+    if let Some(path) = ca_path {
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open CA certificate file {}.", path.display()))?;
+        let mut reader = BufReader::new(file);
+        for cert in rustls_pemfile::certs(&mut reader) {
+            let cert = cert?;
+            certs.push(Certificate::from_der(cert.as_ref()).to_owned());
+        }
+    }
+
+    Ok(certs)
+}
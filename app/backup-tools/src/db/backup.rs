@@ -1,30 +1,310 @@
 use crate::app_config::AppConfig;
-use crate::db::{mongo, pgsql};
-use anyhow::{Context, Result};
-use crossbeam::channel::Receiver;
-use tracing::info;
+use crate::common::BackupPhase;
+use crate::db::custom::CustomBackup;
+use crate::db::database_backup::DatabaseBackup;
+use crate::db::http_export::HttpExportBackup;
+use crate::db::mongo::MongoBackup;
+use crate::db::mysql::MysqlBackup;
+use crate::db::pgsql::PostgresBackup;
+use crate::db::redis::RedisBackup;
+use crate::db::sqlite::SqliteBackup;
+use anyhow::{bail, Context, Result};
+use crossbeam::channel::{bounded, unbounded, Receiver};
+use crossbeam::select;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Instant;
+use tracing::{error, info, Span};
 
-pub fn backup_db(app_config: &AppConfig, shutdown_rx: &Receiver<()>) -> Result<()> {
-    let do_postgres = app_config.postgres_backup_enabled.unwrap_or(false);
-    let do_mongo = app_config.mongo_backup_enabled.unwrap_or(false);
-    let backup_path = app_config.source_path.join("db");
+const DEFAULT_CONCURRENCY: usize = 1;
 
-    if do_postgres || do_mongo {
-        std::fs::create_dir_all(&backup_path)
-            .context("Error while creating top-level database backup directory.")?;
+/// A database backup along with the phase it runs in.
+pub struct PhasedBackup<'a> {
+    pub phase: BackupPhase,
+    backup: Box<dyn DatabaseBackup + 'a>,
+}
+
+impl<'a> PhasedBackup<'a> {
+    fn new(phase: BackupPhase, backup: impl DatabaseBackup + 'a) -> Self {
+        PhasedBackup {
+            phase,
+            backup: Box::new(backup),
+        }
     }
 
-    if do_postgres {
-        pgsql::backup_postgres(&backup_path, shutdown_rx)?;
+    pub fn name(&self) -> &str {
+        self.backup.name()
+    }
+}
+
+/// Reads the configuration of every enabled database backup and assigns each its phase.
+pub fn configured_backups(app_config: &AppConfig) -> Result<Vec<PhasedBackup<'_>>> {
+    let default_phase = app_config.db_backup_phase.unwrap_or_default();
+    let mut backups = Vec::new();
+    let phase = |phase: Option<BackupPhase>| phase.unwrap_or(default_phase);
+
+    if app_config.postgres_backup_enabled.unwrap_or(false) {
+        backups.push(PhasedBackup::new(
+            phase(app_config.postgres_backup_phase),
+            PostgresBackup,
+        ));
     } else {
         info!("PostgreSQL backup disabled.");
     }
 
-    if do_mongo {
-        mongo::backup_mongo(&backup_path, shutdown_rx)?;
+    if app_config.mongo_backup_enabled.unwrap_or(false) {
+        backups.push(PhasedBackup::new(
+            phase(app_config.mongo_backup_phase),
+            MongoBackup,
+        ));
+    } else {
+        info!("MongoDB backup disabled.");
+    }
+
+    if app_config.mysql_backup_enabled.unwrap_or(false) {
+        backups.push(PhasedBackup::new(
+            phase(app_config.mysql_backup_phase),
+            MysqlBackup,
+        ));
+    } else {
+        info!("MySQL backup disabled.");
+    }
+
+    if app_config.redis_backup_enabled.unwrap_or(false) {
+        backups.push(PhasedBackup::new(
+            phase(app_config.redis_backup_phase),
+            RedisBackup,
+        ));
+    } else {
+        info!("Redis backup disabled.");
+    }
+
+    if app_config.sqlite_backup_enabled.unwrap_or(false) {
+        backups.push(PhasedBackup::new(
+            phase(app_config.sqlite_backup_phase),
+            SqliteBackup::new(&app_config.source_path),
+        ));
+    } else {
+        info!("SQLite backup disabled.");
+    }
+
+    if app_config.custom_backup_enabled.unwrap_or(false) {
+        for backup in CustomBackup::from_env()? {
+            backups.push(PhasedBackup::new(phase(backup.phase()), backup));
+        }
+    } else {
+        info!("Custom command backup disabled.");
+    }
+
+    if app_config.http_export_backup_enabled.unwrap_or(false) {
+        for backup in HttpExportBackup::from_env()? {
+            backups.push(PhasedBackup::new(phase(backup.phase()), backup));
+        }
     } else {
-        info!("MongoDB backup disabled.")
+        info!("HTTP export backup disabled.");
+    }
+
+    Ok(backups)
+}
+
+/// Runs the database backups of a phase into `backup_path`, up to `DB_BACKUP_CONCURRENCY` at a time.
+///
+/// Returns the paths, relative to the source path, of files that should be excluded from the file backup because
+/// a consistent copy of them was made here.
+pub fn backup_db(
+    app_config: &AppConfig,
+    backups: &[PhasedBackup],
+    phase: BackupPhase,
+    backup_path: &Path,
+    shutdown_rx: &Receiver<()>,
+) -> Result<Vec<PathBuf>> {
+    let backups: Vec<&dyn DatabaseBackup> = backups
+        .iter()
+        .filter(|b| b.phase == phase)
+        .map(|b| b.backup.as_ref())
+        .collect();
+    if backups.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    std::fs::create_dir_all(backup_path)
+        .context("Error while creating top-level database backup directory.")?;
+
+    let concurrency = app_config
+        .db_backup_concurrency
+        .unwrap_or(DEFAULT_CONCURRENCY)
+        .max(1);
+    run_backups(&backups, backup_path, concurrency, shutdown_rx)
+}
+
+/// Runs the backups on up to `concurrency` worker threads. No further backups are started once one fails, and every
+/// failure is reported.
+fn run_backups(
+    backups: &[&dyn DatabaseBackup],
+    backup_path: &Path,
+    concurrency: usize,
+    shutdown_rx: &Receiver<()>,
+) -> Result<Vec<PathBuf>> {
+    let workers = concurrency.min(backups.len());
+    let (work_tx, work_rx) = unbounded();
+    backups
+        .iter()
+        .for_each(|b| work_tx.send(*b).expect("Work queue was disconnected."));
+    drop(work_tx);
+
+    let (worker_shutdown_tx, worker_shutdown_rx) = unbounded();
+    let (done_tx, done_rx) = bounded::<()>(0);
+    let failed = AtomicBool::new(false);
+    let span = Span::current();
+
+    let results: Vec<(String, Result<Vec<PathBuf>>)> = thread::scope(|scope| {
+        // Each notification is only received once, so it is repeated for every worker that may be waiting on it.
+        scope.spawn(|| {
+            select! {
+                recv(shutdown_rx) -> _ => {
+                    for _ in 0..workers {
+                        worker_shutdown_tx.send(()).unwrap_or(());
+                    }
+                },
+                recv(done_rx) -> _ => {}
+            }
+        });
+
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                let span = span.clone();
+                let (work_rx, worker_shutdown_rx, failed) =
+                    (&work_rx, &worker_shutdown_rx, &failed);
+                scope.spawn(move || {
+                    let _entered = span.enter();
+                    let mut results = Vec::new();
+                    for backup in work_rx.iter() {
+                        if failed.load(Ordering::SeqCst) {
+                            break;
+                        }
+
+                        let start = Instant::now();
+                        let result = backup.run_backup(backup_path, worker_shutdown_rx);
+                        info!(
+                            name = backup.name(),
+                            success = result.is_ok(),
+                            time_elapsed_seconds = start.elapsed().as_secs(),
+                            "Finished database backup."
+                        );
+                        if result.is_err() {
+                            failed.store(true, Ordering::SeqCst);
+                        }
+                        results.push((String::from(backup.name()), result));
+                    }
+                    results
+                })
+            })
+            .collect();
+
+        let results = handles
+            .into_iter()
+            .flat_map(|h| h.join().expect("Database backup worker panicked."))
+            .collect();
+        drop(done_tx);
+        results
+    });
+
+    let mut excludes = Vec::new();
+    let mut failures = Vec::new();
+    for (name, result) in results {
+        match result {
+            Ok(backup_excludes) => excludes.extend(backup_excludes),
+            Err(e) => {
+                error!(ex=?e, name, "Database backup failed.");
+                failures.push(name);
+            }
+        }
+    }
+
+    if !failures.is_empty() {
+        bail!("Database backup failed for: {}", failures.join(", "));
+    }
+
+    Ok(excludes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::run_backups;
+    use crate::db::database_backup::DatabaseBackup;
+    use anyhow::{bail, Result};
+    use crossbeam::channel::{unbounded, Receiver};
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    struct FakeBackup<'a> {
+        name: String,
+        fail: bool,
+        running: &'a AtomicUsize,
+        max_running: &'a AtomicUsize,
+    }
+
+    impl DatabaseBackup for FakeBackup<'_> {
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        fn run_backup(&self, _: &Path, _: &Receiver<()>) -> Result<Vec<PathBuf>> {
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_running.fetch_max(running, Ordering::SeqCst);
+            sleep(Duration::from_millis(50));
+            self.running.fetch_sub(1, Ordering::SeqCst);
+
+            if self.fail {
+                bail!("{} failed", self.name);
+            }
+            Ok(vec![PathBuf::from(&self.name)])
+        }
+    }
+
+    fn fakes<'a>(
+        count: usize,
+        failing: Option<usize>,
+        running: &'a AtomicUsize,
+        max_running: &'a AtomicUsize,
+    ) -> Vec<FakeBackup<'a>> {
+        (0..count)
+            .map(|i| FakeBackup {
+                name: format!("db{}", i),
+                fail: failing == Some(i),
+                running,
+                max_running,
+            })
+            .collect()
     }
 
-    Ok(())
+    #[test]
+    fn run_backups_respects_concurrency_and_collects_excludes() {
+        let (running, max_running) = (AtomicUsize::new(0), AtomicUsize::new(0));
+        let fakes = fakes(5, None, &running, &max_running);
+        let backups: Vec<&dyn DatabaseBackup> = fakes.iter().map(|f| f as _).collect();
+        let (_tx, rx) = unbounded();
+
+        let mut excludes = run_backups(&backups, Path::new("/source/db"), 2, &rx).unwrap();
+        excludes.sort();
+
+        assert_eq!(max_running.load(Ordering::SeqCst), 2);
+        assert_eq!(excludes.len(), 5);
+        assert_eq!(excludes[0], PathBuf::from("db0"));
+    }
+
+    #[test]
+    fn run_backups_given_failure_stops_starting_backups() {
+        let (running, max_running) = (AtomicUsize::new(0), AtomicUsize::new(0));
+        let fakes = fakes(3, Some(0), &running, &max_running);
+        let backups: Vec<&dyn DatabaseBackup> = fakes.iter().map(|f| f as _).collect();
+        let (_tx, rx) = unbounded();
+
+        let error = run_backups(&backups, Path::new("/source/db"), 1, &rx).unwrap_err();
+
+        assert_eq!(error.to_string(), "Database backup failed for: db0");
+    }
 }
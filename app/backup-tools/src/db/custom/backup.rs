@@ -0,0 +1,238 @@
+use crate::common::output::{capture_stdout, join_capture};
+use crate::common::process::{create_command, wait_for_child_exit_codes};
+use crate::common::redact::{redacted, SecretCommandExt};
+use crate::common::secret_files::is_secret;
+use crate::common::BackupPhase;
+use crate::db::custom::config::{
+    CustomConfig, CustomTargets, CUSTOM_PREFIX, DIR_PLACEHOLDER, NAME_PLACEHOLDER,
+    OUTPUT_PLACEHOLDER,
+};
+use crate::db::database_backup::DatabaseBackup;
+use anyhow::{anyhow, bail, Context, Result};
+use crossbeam::channel::Receiver;
+use envy::prefixed;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{debug, info, info_span};
+
+/// A dumper configured by `CUSTOM_<NAME>_` variables, writing to `db/<name>`.
+pub struct CustomBackup {
+    name: String,
+    config: CustomConfig,
+}
+
+impl CustomBackup {
+    /// Reads every dumper named in `CUSTOM_TARGETS`.
+    pub fn from_env() -> Result<Vec<CustomBackup>> {
+        let targets = prefixed(CUSTOM_PREFIX)
+            .from_env::<CustomTargets>()
+            .context("Error while reading custom dumper names.")?;
+
+        targets
+            .targets
+            .unwrap_or_default()
+            .into_iter()
+            .map(|name| {
+                let prefix = format!(
+                    "{}{}_",
+                    CUSTOM_PREFIX,
+                    name.replace('-', "_").to_uppercase()
+                );
+                let config = prefixed(&prefix)
+                    .from_env::<CustomConfig>()
+                    .with_context(|| format!("Error while reading custom dumper {}.", name))?;
+                config.validate(&name)?;
+
+                Ok(CustomBackup { name, config })
+            })
+            .collect()
+    }
+
+    pub fn phase(&self) -> Option<BackupPhase> {
+        self.config.phase
+    }
+}
+
+impl DatabaseBackup for CustomBackup {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn run_backup(
+        &self,
+        base_backup_path: &Path,
+        shutdown_rx: &Receiver<()>,
+    ) -> Result<Vec<PathBuf>> {
+        let span = info_span!("custom", name = %self.name);
+        let _entered = span.enter();
+
+        info!("Starting custom database backup.");
+        let output_dir = base_backup_path.join(&self.name);
+        fs::create_dir_all(&output_dir)
+            .context("Error while creating path to custom database backup.")?;
+        let output_path = output_dir.join(self.config.output_file_name());
+        // A leftover file from a previous run would hide a dump that was never written.
+        if output_path.exists() {
+            fs::remove_file(&output_path).context("Error while removing previous dump.")?;
+        }
+
+        run_command(
+            &self.name,
+            &self.config,
+            &output_dir,
+            &output_path,
+            shutdown_rx,
+        )?;
+
+        if !output_path.is_file() {
+            bail!(
+                "The command did not write its dump to {}.",
+                output_path.display()
+            );
+        }
+
+        info!(path=%output_path.display(), "Finished custom database backup.");
+        Ok(Vec::new())
+    }
+}
+
+fn run_command(
+    name: &str,
+    config: &CustomConfig,
+    output_dir: &Path,
+    output_path: &Path,
+    shutdown_rx: &Receiver<()>,
+) -> Result<()> {
+    let args = expand_command(&config.command, name, output_dir, output_path)?;
+    let (program, args) = args
+        .split_first()
+        .ok_or_else(|| anyhow!("The command of custom dumper {} is empty.", name))?;
+
+    let mut process = create_command(program);
+    process
+        .args(args)
+        .current_dir(config.working_dir.as_deref().unwrap_or(output_dir));
+    for (key, value) in config.env_vars() {
+        if is_secret(key) {
+            process.secret_env(key, value);
+        } else {
+            process.env(key, value);
+        }
+    }
+
+    debug!("Final {} command: {:?}", program, redacted(&process));
+
+    let mut child = process
+        .spawn()
+        .with_context(|| format!("Error while starting {} process.", program))?;
+
+    let capture = if config.captures_stdout() {
+        Some(capture_stdout(
+            &mut child,
+            output_path,
+            config.compress.unwrap_or(false),
+        )?)
+    } else {
+        None
+    };
+
+    let success_codes = config.success_exit_codes.as_deref().unwrap_or(&[0]);
+    let wait_result = wait_for_child_exit_codes(
+        child,
+        config.timeout.map(Duration::from_secs),
+        shutdown_rx,
+        success_codes,
+    );
+
+    if let Some(capture) = capture {
+        let bytes = join_capture(capture)?;
+        debug!(bytes, "Captured dump from stdout.");
+    }
+
+    wait_result
+}
+
+/// Splits a command line into arguments, honoring single and double quotes, and replaces the placeholders within each
+/// argument so that paths containing spaces remain a single argument.
+fn expand_command(
+    command: &str,
+    name: &str,
+    output_dir: &Path,
+    output_path: &Path,
+) -> Result<Vec<String>> {
+    let output_dir = output_dir.to_string_lossy();
+    let output_path = output_path.to_string_lossy();
+
+    Ok(split_command(command)?
+        .into_iter()
+        .map(|arg| {
+            arg.replace(OUTPUT_PLACEHOLDER, &output_path)
+                .replace(DIR_PLACEHOLDER, &output_dir)
+                .replace(NAME_PLACEHOLDER, name)
+        })
+        .collect())
+}
+
+fn split_command(command: &str) -> Result<Vec<String>> {
+    let mut args = Vec::new();
+    let mut current: Option<String> = None;
+    let mut quote: Option<char> = None;
+
+    for c in command.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => current.get_or_insert_with(String::new).push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                current.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => args.extend(current.take()),
+            (None, c) => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+
+    if quote.is_some() {
+        bail!("Unterminated quote in command: {}", command);
+    }
+    args.extend(current);
+
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expand_command, split_command};
+    use std::path::Path;
+
+    #[test]
+    fn split_command_honors_quotes() {
+        assert_eq!(
+            split_command(r#"influx backup --token "a b" '' --org=home"#).unwrap(),
+            vec!["influx", "backup", "--token", "a b", "", "--org=home"]
+        );
+        assert!(split_command("slapcat -b 'dc=home").is_err());
+    }
+
+    #[test]
+    fn expand_command_replaces_placeholders_within_arguments() {
+        let args = expand_command(
+            "etcdctl snapshot save {output} --name={name}",
+            "etcd",
+            Path::new("/source/db/etcd"),
+            Path::new("/source/db/etcd/my snapshot.db"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            args,
+            vec![
+                "etcdctl",
+                "snapshot",
+                "save",
+                "/source/db/etcd/my snapshot.db",
+                "--name=etcd"
+            ]
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use crate::common::BackupPhase;
+use crate::db::database_backup::validate_dumper_name;
+use anyhow::{bail, Result};
+use serde::Deserialize;
+use std::path::PathBuf;
+
+pub const CUSTOM_PREFIX: &str = "CUSTOM_";
+pub const DEFAULT_OUTPUT_FILE: &str = "dump";
+pub const OUTPUT_PLACEHOLDER: &str = "{output}";
+pub const DIR_PLACEHOLDER: &str = "{dir}";
+pub const NAME_PLACEHOLDER: &str = "{name}";
+
+/// Names of the custom dumpers, each configured with variables prefixed by `CUSTOM_<NAME>_`.
+#[derive(Debug, Deserialize)]
+pub struct CustomTargets {
+    pub targets: Option<Vec<String>>,
+}
+
+/// A dumper for a database without a dedicated module, run as an arbitrary command.
+#[derive(Debug, Default, Deserialize)]
+pub struct CustomConfig {
+    /// The command line to run, where `{output}`, `{dir}` and `{name}` are replaced by the output file, the output
+    /// directory and the name of the dumper.
+    pub command: String,
+    /// Additional `KEY=VALUE` environment variables for the command.
+    pub env: Option<Vec<String>>,
+    /// The directory the command runs in; defaults to the output directory.
+    pub working_dir: Option<PathBuf>,
+    /// The name of the file within the output directory that the dump is written to.
+    pub output_file: Option<String>,
+    /// Whether the command's stdout is the dump; defaults to `true` unless the command contains `{output}`.
+    pub capture_stdout: Option<bool>,
+    /// Whether captured stdout is gzip compressed.
+    pub compress: Option<bool>,
+    pub timeout: Option<u64>,
+    /// Exit codes that indicate success; defaults to `0`.
+    pub success_exit_codes: Option<Vec<i32>>,
+    /// Overrides `DB_BACKUP_PHASE` for this custom dumper.
+    pub phase: Option<BackupPhase>,
+}
+
+impl CustomConfig {
+    pub fn validate(&self, name: &str) -> Result<()> {
+        validate_dumper_name(name)?;
+        if self.command.trim().is_empty() {
+            bail!("A command must be configured for custom dumper {}.", name);
+        }
+        if let Some(entry) = self.env.iter().flatten().find(|e| !e.contains('=')) {
+            bail!("Environment entry {} must have the form KEY=VALUE.", entry);
+        }
+        if self.compress.unwrap_or(false) && !self.captures_stdout() {
+            bail!("Only captured stdout can be compressed.");
+        }
+
+        Ok(())
+    }
+
+    pub fn captures_stdout(&self) -> bool {
+        self.capture_stdout
+            .unwrap_or_else(|| !self.command.contains(OUTPUT_PLACEHOLDER))
+    }
+
+    /// The name of the dump file, with `.gz` appended when compressed.
+    pub fn output_file_name(&self) -> String {
+        let name = self.output_file.as_deref().unwrap_or(DEFAULT_OUTPUT_FILE);
+        if self.compress.unwrap_or(false) {
+            format!("{}.gz", name)
+        } else {
+            String::from(name)
+        }
+    }
+
+    pub fn env_vars(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.env.iter().flatten().filter_map(|e| e.split_once('='))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CustomConfig;
+
+    fn config(command: &str) -> CustomConfig {
+        CustomConfig {
+            command: String::from(command),
+            ..CustomConfig::default()
+        }
+    }
+
+    #[test]
+    fn captures_stdout_defaults_to_commands_without_output_placeholder() {
+        assert!(config("slapcat -n 1").captures_stdout());
+        assert!(!config("etcdctl snapshot save {output}").captures_stdout());
+    }
+
+    #[test]
+    fn validate_rejects_reserved_names_and_malformed_env() {
+        assert!(config("slapcat").validate("ldap").is_ok());
+        assert!(config("slapcat").validate("postgres").is_err());
+        assert!(config("slapcat").validate("../ldap").is_err());
+
+        let config = CustomConfig {
+            env: Some(vec![String::from("ETCDCTL_API")]),
+            ..config("etcdctl snapshot save {output}")
+        };
+        assert!(config.validate("etcd").is_err());
+    }
+
+    #[test]
+    fn output_file_name_adds_gz_when_compressed() {
+        let config = CustomConfig {
+            output_file: Some(String::from("ldap.ldif")),
+            compress: Some(true),
+            ..config("slapcat")
+        };
+        assert_eq!(config.output_file_name(), "ldap.ldif.gz");
+    }
+}
@@ -0,0 +1,39 @@
+use anyhow::{bail, Result};
+use crossbeam::channel::Receiver;
+use std::path::{Path, PathBuf};
+
+/// Directories under `db` written by the built-in modules, which configurable dumpers may not use.
+const RESERVED_NAMES: [&str; 5] = ["postgres", "mongo", "mysql", "redis", "sqlite"];
+
+/// A dump into the `db` directory chosen by `DB_DUMP_LOCATION`: within the source path, the staging path, or the new
+/// backup. Dumps may run concurrently on worker threads.
+pub trait DatabaseBackup: Send + Sync {
+    /// The name of the database type or dumper, used in logs.
+    fn name(&self) -> &str;
+
+    /// Dumps into `base_backup_path`, the `db` directory of the dump location, and returns the paths, relative to the
+    /// source path, of files that should be excluded from the file backup.
+    fn run_backup(
+        &self,
+        base_backup_path: &Path,
+        shutdown_rx: &Receiver<()>,
+    ) -> Result<Vec<PathBuf>>;
+}
+
+/// Checks that the name of a configurable dumper can be used as its directory under `db`.
+pub fn validate_dumper_name(name: &str) -> Result<()> {
+    if RESERVED_NAMES.contains(&name) {
+        bail!(
+            "Dumper name {} is used by a built-in database backup.",
+            name
+        );
+    }
+    if name.is_empty() || name.contains(['/', '.']) {
+        bail!(
+            "Dumper name {} must not be empty or contain '/' or '.'.",
+            name
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,337 @@
+use crate::common::tls;
+use crate::common::BackupPhase;
+use crate::db::database_backup::DatabaseBackup;
+use crate::db::http_export::config::{
+    HttpExportConfig, HttpExportTargets, HTTP_EXPORT_PREFIX, ID_PLACEHOLDER,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use crossbeam::channel::{after, Receiver};
+use crossbeam::select;
+use envy::prefixed;
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{debug, info, info_span, warn};
+use ureq::http::{Response, StatusCode};
+use ureq::tls::{RootCerts, TlsConfig};
+use ureq::{Agent, Body};
+
+const BUFFER_SIZE: usize = 64 * 1024;
+
+/// A backup downloaded over HTTP as configured by `HTTP_EXPORT_<NAME>_` variables, writing to `db/<name>`.
+pub struct HttpExportBackup {
+    name: String,
+    config: HttpExportConfig,
+}
+
+impl HttpExportBackup {
+    /// Reads every export named in `HTTP_EXPORT_TARGETS`.
+    pub fn from_env() -> Result<Vec<HttpExportBackup>> {
+        let targets = prefixed(HTTP_EXPORT_PREFIX)
+            .from_env::<HttpExportTargets>()
+            .context("Error while reading HTTP export names.")?;
+
+        targets
+            .targets
+            .unwrap_or_default()
+            .into_iter()
+            .map(|name| {
+                let prefix = format!(
+                    "{}{}_",
+                    HTTP_EXPORT_PREFIX,
+                    name.replace('-', "_").to_uppercase()
+                );
+                let config = prefixed(&prefix)
+                    .from_env::<HttpExportConfig>()
+                    .with_context(|| format!("Error while reading HTTP export {}.", name))?;
+                config.validate(&name)?;
+
+                Ok(HttpExportBackup { name, config })
+            })
+            .collect()
+    }
+
+    pub fn phase(&self) -> Option<BackupPhase> {
+        self.config.phase
+    }
+}
+
+impl DatabaseBackup for HttpExportBackup {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn run_backup(
+        &self,
+        base_backup_path: &Path,
+        shutdown_rx: &Receiver<()>,
+    ) -> Result<Vec<PathBuf>> {
+        let span = info_span!("http_export", name = %self.name);
+        let _entered = span.enter();
+
+        info!("Starting HTTP export backup.");
+        let output_dir = base_backup_path.join(&self.name);
+        fs::create_dir_all(&output_dir)
+            .context("Error while creating path to HTTP export backup.")?;
+        let output_path = output_dir.join(self.config.output_file_name());
+        let partial_path = output_dir.join(format!("{}.partial", self.config.output_file_name()));
+
+        let exporter = Exporter::new(&self.config)?;
+        let id = match &self.config.export_url {
+            Some(export_url) => exporter.start_export(export_url)?,
+            None => None,
+        };
+        if let Some(poll_url) = &self.config.poll_url {
+            exporter.wait_for_export(&expand_url(poll_url, id.as_deref()), shutdown_rx)?;
+        }
+
+        let result = exporter.download(
+            &expand_url(&self.config.url, id.as_deref()),
+            &partial_path,
+            shutdown_rx,
+        );
+        let bytes = match result {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                if partial_path.exists() {
+                    fs::remove_file(&partial_path).unwrap_or_else(
+                        |ex| warn!(ex=?ex, "Failed to remove incomplete HTTP export."),
+                    );
+                }
+                return Err(e);
+            }
+        };
+
+        // Only replace the previous backup once the new one was downloaded completely.
+        fs::rename(&partial_path, &output_path)
+            .context("Error while moving HTTP export into place.")?;
+
+        info!(bytes, path=%output_path.display(), "Finished HTTP export backup.");
+        Ok(Vec::new())
+    }
+}
+
+struct Exporter<'a> {
+    agent: Agent,
+    authorization: Option<String>,
+    config: &'a HttpExportConfig,
+}
+
+impl<'a> Exporter<'a> {
+    fn new(config: &'a HttpExportConfig) -> Result<Exporter<'a>> {
+        let certs = tls::load_certs(config.ca_file.as_deref())?;
+        let tls_config = TlsConfig::builder()
+            .root_certs(RootCerts::Specific(Arc::new(certs)))
+            .build();
+
+        // Statuses are checked by the caller so that `202 Accepted` can be told apart while polling.
+        let agent_config = Agent::config_builder()
+            .tls_config(tls_config)
+            .timeout_global(Some(config.timeout()))
+            .http_status_as_error(false)
+            .build();
+
+        Ok(Exporter {
+            agent: Agent::from(agent_config),
+            authorization: config.authorization(),
+            config,
+        })
+    }
+
+    /// Starts the export job, returning its ID if an export ID pointer is configured.
+    fn start_export(&self, export_url: &str) -> Result<Option<String>> {
+        info!(url = export_url, "Starting export.");
+        let mut request = self
+            .agent
+            .post(export_url)
+            .header("Accept", "application/json");
+        if let Some(authorization) = &self.authorization {
+            request = request.header("Authorization", authorization);
+        }
+
+        let mut response = match &self.config.export_body {
+            Some(body) => request
+                .header("Content-Type", "application/json")
+                .send(body.as_str()),
+            None => request.send_empty(),
+        }
+        .context("Error while starting export.")?;
+        check_status(&response, "starting export")?;
+
+        let Some(pointer) = &self.config.export_id_pointer else {
+            return Ok(None);
+        };
+        let body = response
+            .body_mut()
+            .read_json::<Value>()
+            .context("Error while reading export response.")?;
+        let id = export_id(&body, pointer)?;
+        debug!(id, "Started export.");
+
+        Ok(Some(id))
+    }
+
+    /// Polls until the export is no longer reported as in progress with `202 Accepted`.
+    fn wait_for_export(&self, poll_url: &str, shutdown_rx: &Receiver<()>) -> Result<()> {
+        let start = Instant::now();
+        loop {
+            let response = self.get(poll_url).context("Error while polling export.")?;
+            if response.status() != StatusCode::ACCEPTED {
+                check_status(&response, "polling export")?;
+                info!(
+                    time_elapsed_seconds = start.elapsed().as_secs(),
+                    "Export is ready."
+                );
+                return Ok(());
+            }
+
+            if start.elapsed() >= self.config.timeout() {
+                bail!("Timed out waiting for export to finish.");
+            }
+            debug!("Export is still in progress.");
+            select! {
+                recv(shutdown_rx) -> _ => {
+                    warn!("Received notification to shutdown, no longer waiting for export.");
+                    bail!("Stopped waiting for export due to shutdown.");
+                },
+                recv(after(self.config.poll_interval())) -> _ => {}
+            }
+        }
+    }
+
+    /// Streams the backup into `path`, checking its content type and size.
+    fn download(&self, url: &str, path: &Path, shutdown_rx: &Receiver<()>) -> Result<u64> {
+        info!(url, "Downloading export.");
+        let mut response = self.get(url).context("Error while downloading export.")?;
+        check_status(&response, "downloading export")?;
+
+        if let Some(expected) = &self.config.content_type {
+            let actual = response.body().mime_type().unwrap_or_default();
+            if !content_type_matches(expected, actual) {
+                bail!(
+                    "Expected export with content type {}, received {}.",
+                    expected,
+                    actual
+                );
+            }
+        }
+
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create output file {}.", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        let mut reader = response.body_mut().as_reader();
+        let mut buffer = vec![0; BUFFER_SIZE];
+        let mut bytes = 0u64;
+        loop {
+            if shutdown_rx.try_recv().is_ok() {
+                bail!("Stopped downloading export due to shutdown.");
+            }
+            let read = reader
+                .read(&mut buffer)
+                .context("Error while reading export.")?;
+            if read == 0 {
+                break;
+            }
+            writer
+                .write_all(&buffer[..read])
+                .context("Error while writing export.")?;
+            bytes += read as u64;
+        }
+        writer.flush().context("Error while writing export.")?;
+
+        let min_size = self.config.min_size.unwrap_or(1);
+        if bytes < min_size {
+            bail!(
+                "Export of {} bytes is smaller than the minimum of {} bytes.",
+                bytes,
+                min_size
+            );
+        }
+
+        Ok(bytes)
+    }
+
+    fn get(&self, url: &str) -> Result<Response<Body>, ureq::Error> {
+        let mut request = self.agent.get(url);
+        if let Some(authorization) = &self.authorization {
+            request = request.header("Authorization", authorization);
+        }
+
+        request.call()
+    }
+}
+
+fn check_status(response: &Response<Body>, action: &str) -> Result<()> {
+    if !response.status().is_success() {
+        bail!(
+            "Received HTTP status {} while {}.",
+            response.status(),
+            action
+        );
+    }
+
+    Ok(())
+}
+
+fn export_id(body: &Value, pointer: &str) -> Result<String> {
+    match body.pointer(pointer) {
+        Some(Value::String(id)) => Ok(id.clone()),
+        Some(Value::Number(id)) => Ok(id.to_string()),
+        _ => Err(anyhow!(
+            "Export response has no string or number at {}.",
+            pointer
+        )),
+    }
+}
+
+fn expand_url(url: &str, id: Option<&str>) -> String {
+    match id {
+        Some(id) => url.replace(
+            ID_PLACEHOLDER,
+            &percent_encoding::utf8_percent_encode(id, percent_encoding::NON_ALPHANUMERIC)
+                .to_string(),
+        ),
+        None => String::from(url),
+    }
+}
+
+fn content_type_matches(expected: &str, actual: &str) -> bool {
+    expected.trim().eq_ignore_ascii_case(actual.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{content_type_matches, expand_url, export_id};
+    use serde_json::json;
+
+    #[test]
+    fn export_id_reads_strings_and_numbers() {
+        let body = json!({"data": {"slug": "a1b2c3"}, "id": 42});
+
+        assert_eq!(export_id(&body, "/data/slug").unwrap(), "a1b2c3");
+        assert_eq!(export_id(&body, "/id").unwrap(), "42");
+        assert!(export_id(&body, "/data").is_err());
+        assert!(export_id(&body, "/missing").is_err());
+    }
+
+    #[test]
+    fn expand_url_encodes_id() {
+        assert_eq!(
+            expand_url("http://supervisor/backups/{id}/download", Some("a b")),
+            "http://supervisor/backups/a%20b/download"
+        );
+        assert_eq!(expand_url("https://gitea/dump", None), "https://gitea/dump");
+    }
+
+    #[test]
+    fn content_type_matches_ignores_case() {
+        assert!(content_type_matches(
+            "application/x-tar",
+            "Application/X-Tar"
+        ));
+        assert!(!content_type_matches("application/x-tar", "text/html"));
+    }
+}
@@ -0,0 +1,177 @@
+use crate::common::BackupPhase;
+use crate::db::database_backup::validate_dumper_name;
+use anyhow::{bail, Result};
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::time::Duration;
+
+pub const HTTP_EXPORT_PREFIX: &str = "HTTP_EXPORT_";
+pub const ID_PLACEHOLDER: &str = "{id}";
+const DEFAULT_OUTPUT_FILE: &str = "export";
+const DEFAULT_POLL_INTERVAL_SECS: u64 = 5;
+const DEFAULT_TIMEOUT_SECS: u64 = 600;
+const URL_SCHEMES: [&str; 2] = ["http://", "https://"];
+
+/// Names of the HTTP exports, each configured with variables prefixed by `HTTP_EXPORT_<NAME>_`.
+#[derive(Debug, Deserialize)]
+pub struct HttpExportTargets {
+    pub targets: Option<Vec<String>>,
+}
+
+/// An application backup downloaded over HTTP, optionally after starting an export job and waiting for it to finish.
+#[derive(Debug, Default, Deserialize)]
+pub struct HttpExportConfig {
+    /// The URL the backup is downloaded from.
+    pub url: String,
+    /// A bearer token, usually read from `HTTP_EXPORT_<NAME>_TOKEN_FILE`.
+    pub token: Option<String>,
+    pub username: Option<String>,
+    /// The password for basic authentication, usually read from `HTTP_EXPORT_<NAME>_PASSWORD_FILE`.
+    pub password: Option<String>,
+    /// A PEM file of additional certificates to trust.
+    pub ca_file: Option<PathBuf>,
+    /// A URL that is sent a POST request to start the export before polling and downloading.
+    pub export_url: Option<String>,
+    /// A JSON body for the export request.
+    pub export_body: Option<String>,
+    /// A JSON pointer to the ID of the export job in the export response, which replaces `{id}` in the other URLs.
+    pub export_id_pointer: Option<String>,
+    /// A URL that is polled until it stops responding with `202 Accepted`.
+    pub poll_url: Option<String>,
+    pub poll_interval: Option<u64>,
+    /// The name of the file within the output directory that the backup is written to.
+    pub output_file: Option<String>,
+    /// The media type the download must have, e.g. `application/x-tar`.
+    pub content_type: Option<String>,
+    /// The minimum size of the download in bytes; defaults to `1` to reject empty responses.
+    pub min_size: Option<u64>,
+    /// Seconds each request may take, which also bounds the time spent polling.
+    pub timeout: Option<u64>,
+    /// Overrides `DB_BACKUP_PHASE` for this export.
+    pub phase: Option<BackupPhase>,
+}
+
+impl HttpExportConfig {
+    pub fn validate(&self, name: &str) -> Result<()> {
+        validate_dumper_name(name)?;
+
+        let urls = [
+            Some(&self.url),
+            self.export_url.as_ref(),
+            self.poll_url.as_ref(),
+        ];
+        if let Some(url) = urls
+            .iter()
+            .flatten()
+            .find(|url| !URL_SCHEMES.iter().any(|scheme| url.starts_with(scheme)))
+        {
+            bail!(
+                "HTTP export URL {} must start with http:// or https://.",
+                url
+            );
+        }
+
+        if self.token.is_some() && (self.username.is_some() || self.password.is_some()) {
+            bail!("A token cannot be combined with a username or password.");
+        }
+        if self.username.is_some() != self.password.is_some() {
+            bail!("Basic authentication requires both a username and a password.");
+        }
+
+        if self.export_url.is_none()
+            && (self.export_body.is_some()
+                || self.export_id_pointer.is_some()
+                || self.poll_url.is_some())
+        {
+            bail!("An export body, export ID pointer or poll URL requires an export URL.");
+        }
+        if self
+            .export_id_pointer
+            .as_ref()
+            .is_some_and(|p| !p.starts_with('/'))
+        {
+            bail!("The export ID pointer must be a JSON pointer starting with '/'.");
+        }
+        let uses_id = self.url.contains(ID_PLACEHOLDER)
+            || self
+                .poll_url
+                .as_ref()
+                .is_some_and(|url| url.contains(ID_PLACEHOLDER));
+        if uses_id && self.export_id_pointer.is_none() {
+            bail!(
+                "URLs containing {} require an export ID pointer.",
+                ID_PLACEHOLDER
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Builds the value of the `Authorization` header.
+    pub fn authorization(&self) -> Option<String> {
+        if let Some(token) = &self.token {
+            return Some(format!("Bearer {}", token));
+        }
+
+        match (&self.username, &self.password) {
+            (Some(username), Some(password)) => {
+                let credentials = format!("{}:{}", username, password);
+                Some(format!("Basic {}", BASE64_STANDARD.encode(credentials)))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn output_file_name(&self) -> &str {
+        self.output_file.as_deref().unwrap_or(DEFAULT_OUTPUT_FILE)
+    }
+
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_secs(self.poll_interval.unwrap_or(DEFAULT_POLL_INTERVAL_SECS))
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout.unwrap_or(DEFAULT_TIMEOUT_SECS))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HttpExportConfig;
+
+    fn config(url: &str) -> HttpExportConfig {
+        HttpExportConfig {
+            url: String::from(url),
+            ..HttpExportConfig::default()
+        }
+    }
+
+    #[test]
+    fn validate_given_id_placeholder_requires_export_id_pointer() {
+        let config = HttpExportConfig {
+            export_url: Some(String::from("http://supervisor/backups/new/full")),
+            ..config("http://supervisor/backups/{id}/download")
+        };
+        assert!(config.validate("homeassistant").is_err());
+
+        let config = HttpExportConfig {
+            export_id_pointer: Some(String::from("/data/slug")),
+            ..config
+        };
+        assert!(config.validate("homeassistant").is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_mixed_authentication_and_unknown_schemes() {
+        assert!(config("ftp://gitea/dump").validate("gitea").is_err());
+
+        let config = HttpExportConfig {
+            token: Some(String::from("token")),
+            username: Some(String::from("backup")),
+            ..config("https://gitea/dump")
+        };
+        assert!(config.validate("gitea").is_err());
+    }
+}
@@ -1,5 +1,11 @@
 mod backup;
+mod custom;
+mod database_backup;
+mod http_export;
 mod mongo;
+mod mysql;
 mod pgsql;
+mod redis;
+mod sqlite;
 
-pub use backup::backup_db;
+pub use backup::{backup_db, configured_backups, PhasedBackup};
@@ -1,4 +1,4 @@
 mod backup;
 mod config;
 
-pub use backup::backup_mongo;
+pub use backup::MongoBackup;
@@ -0,0 +1,123 @@
+use crate::common::output::{capture_stdout, join_capture};
+use crate::common::process::{create_command, wait_for_child_success};
+use crate::common::redact::{redacted, SecretCommandExt};
+use crate::db::database_backup::DatabaseBackup;
+use crate::db::mysql::config;
+use crate::db::mysql::config::{MysqlConfig, MysqlUrlExtras};
+use anyhow::{Context, Result};
+use crossbeam::channel::Receiver;
+use envy::prefixed;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Child;
+use std::time::Duration;
+use tracing::{debug, info, trace_span};
+
+const ALL_DATABASES_NAME: &str = "all-databases";
+const DUMP_FILE_NAME: &str = "mysql.sql.gz";
+
+pub struct MysqlBackup;
+
+impl DatabaseBackup for MysqlBackup {
+    fn name(&self) -> &str {
+        "MySQL"
+    }
+
+    fn run_backup(
+        &self,
+        base_backup_path: &Path,
+        shutdown_rx: &Receiver<()>,
+    ) -> Result<Vec<PathBuf>> {
+        backup_mysql(base_backup_path, shutdown_rx).map(|_| Vec::new())
+    }
+}
+
+fn backup_mysql(base_backup_path: &Path, shutdown_rx: &Receiver<()>) -> Result<()> {
+    let span = trace_span!("mysql");
+    let _entered = span.enter();
+
+    info!("Starting MySQL backup.");
+    let config = get_mysql_config()?;
+
+    match &config.database_names {
+        Some(names) if !names.is_empty() => {
+            for db_name in names {
+                start_mysql_backup(&config, Some(db_name), base_backup_path, shutdown_rx)?;
+            }
+        }
+        _ => start_mysql_backup(&config, None, base_backup_path, shutdown_rx)?,
+    }
+
+    Ok(())
+}
+
+fn get_mysql_config() -> Result<MysqlConfig> {
+    env::var(config::MYSQL_ENV_URL).map_or_else(
+        |_| {
+            prefixed(config::MYSQL_PREFIX)
+                .from_env()
+                .context("Error while mapping MysqlConfig from individual env vars.")
+        },
+        |s| {
+            let extras = prefixed(config::MYSQL_PREFIX)
+                .from_env::<MysqlUrlExtras>()
+                .context("Error while mapping MysqlConfig from individual env vars.")?;
+            MysqlConfig::from_url(&s, extras.dump_command, extras.timeout)
+                .context("Error while mapping MysqlConfig from URL.")
+        },
+    )
+}
+
+fn execute_mysqldump(config: &MysqlConfig, database_name: Option<&str>) -> Result<Child> {
+    let port = &config.port.unwrap_or(config::DEFAULT_MYSQL_PORT);
+    let program = config
+        .dump_command
+        .as_deref()
+        .unwrap_or(config::DEFAULT_DUMP_COMMAND);
+
+    let mut process = create_command(program);
+    let mut process_ref = &mut process;
+
+    process_ref
+        .secret_env("MYSQL_PWD", &config.password)
+        .args(["-h", &config.host])
+        .args(["-P", &port.to_string()])
+        .args(["-u", &config.username])
+        .arg("--single-transaction")
+        .arg("--routines")
+        .arg("--events");
+
+    process_ref = match database_name {
+        Some(db) => process_ref.args(["--databases", db]),
+        None => process_ref.arg("--all-databases"),
+    };
+
+    debug!("Final {} command: {:?}", program, redacted(process_ref));
+
+    process_ref
+        .spawn()
+        .with_context(|| format!("Error while starting {} process.", program))
+}
+
+fn start_mysql_backup(
+    config: &MysqlConfig,
+    database_name: Option<&str>,
+    base_backup_path: &Path,
+    shutdown_rx: &Receiver<()>,
+) -> Result<()> {
+    let name = database_name.unwrap_or(ALL_DATABASES_NAME);
+    let backup_path = base_backup_path.join(format!("mysql/{}", name));
+    std::fs::create_dir_all(&backup_path).context("Error while creating path to MySQL backup.")?;
+
+    info!(database = name, "Dumping MySQL database.");
+    let mut process = execute_mysqldump(config, database_name)?;
+    let capture = capture_stdout(&mut process, &backup_path.join(DUMP_FILE_NAME), true)?;
+    let timeout = config.timeout.map(Duration::from_secs);
+
+    let wait_result = wait_for_child_success(process, timeout, shutdown_rx);
+    let capture_result = join_capture(capture);
+    wait_result?;
+    capture_result?;
+
+    Ok(())
+}
@@ -0,0 +1,4 @@
+mod backup;
+mod config;
+
+pub use backup::MysqlBackup;
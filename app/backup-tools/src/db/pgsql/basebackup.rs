@@ -0,0 +1,164 @@
+use crate::common::process::wait_for_child_success;
+use crate::common::redact::redacted;
+use crate::db::pgsql::client::PgClient;
+use crate::db::pgsql::config::{CompressionMethod, PostgresOptions, PostgresTarget};
+use anyhow::{bail, Context, Result};
+use crossbeam::channel::Receiver;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{debug, info};
+
+const BASEBACKUP_DIR: &str = "basebackup";
+/// The first major version of `pg_verifybackup` that can verify tar format backups.
+const TAR_VERIFICATION_VERSION: u32 = 18;
+
+/// Takes a physical backup of the whole cluster with `pg_basebackup` into `<server_path>/basebackup` and, unless
+/// disabled, verifies it against its backup manifest with `pg_verifybackup`.
+///
+/// The backup is written in tar format with the WAL needed to make it consistent streamed alongside it, so that it can
+/// be restored by extracting the archives into an empty data directory.
+pub fn run_basebackup(
+    client: &PgClient,
+    target: &PostgresTarget,
+    server_path: &Path,
+    shutdown_rx: &Receiver<()>,
+) -> Result<PathBuf> {
+    let backup_path = server_path.join(BASEBACKUP_DIR);
+    // The verifying client is found first so that a backup is not taken only to fail afterwards.
+    let verifier = if target.options.verify_backup.unwrap_or(true) {
+        Some(verification_client(client, &target.options)?)
+    } else {
+        None
+    };
+
+    // pg_basebackup refuses to write into a non-empty directory.
+    if backup_path.exists() {
+        fs::remove_dir_all(&backup_path)
+            .context("Error while removing previous PostgreSQL base backup.")?;
+    }
+    fs::create_dir_all(server_path).context("Error while creating path to PostgreSQL backup.")?;
+
+    let mut process = client.command("pg_basebackup", &target.config);
+    process
+        .arg("-w")
+        .arg("-D")
+        .arg(backup_path.as_os_str())
+        .args(basebackup_option_args(&target.options));
+
+    debug!("Final pg_basebackup command: {:?}", redacted(&process));
+
+    let child = process
+        .spawn()
+        .context("Error while starting pg_basebackup process.")?;
+    wait_for_child_success(child, target.options.timeout(), shutdown_rx)?;
+
+    if let Some(verifier) = verifier {
+        verify_backup(
+            &verifier,
+            &backup_path,
+            target.options.timeout(),
+            shutdown_rx,
+        )
+        .context("PostgreSQL base backup failed manifest verification.")?;
+        info!("Verified PostgreSQL base backup against its manifest.");
+    }
+
+    Ok(backup_path)
+}
+
+/// Selects the client programs whose `pg_verifybackup` can verify tar format backups, which is the resolved client
+/// unless it is too old and a newer one is installed.
+fn verification_client(client: &PgClient, options: &PostgresOptions) -> Result<PgClient> {
+    if client.is_at_least(TAR_VERIFICATION_VERSION) {
+        return Ok(client.clone());
+    }
+
+    match PgClient::find_newest("pg_verifybackup", TAR_VERIFICATION_VERSION, options) {
+        Some(verifier) => {
+            info!("Using a newer pg_verifybackup to verify the tar format base backup.");
+            Ok(verifier)
+        }
+        None => bail!(
+            "Verifying tar format base backups requires pg_verifybackup {} or newer; disable POSTGRES_VERIFY_BACKUP \
+            or install a newer client in one of the POSTGRES_CLIENT_DIRS.",
+            TAR_VERIFICATION_VERSION
+        ),
+    }
+}
+
+fn basebackup_option_args(options: &PostgresOptions) -> Vec<String> {
+    let mut args = vec![
+        String::from("-F"),
+        String::from("t"),
+        String::from("-X"),
+        String::from("stream"),
+    ];
+
+    // Unlike pg_dump, pg_basebackup does not compress tar archives by default.
+    let method = options.compression.unwrap_or(CompressionMethod::Gzip);
+    if method != CompressionMethod::None {
+        let compress = match options.compression_level {
+            Some(level) => format!("--compress=client-{}:{}", method.name(), level),
+            None => format!("--compress=client-{}", method.name()),
+        };
+        args.push(compress);
+    }
+
+    args
+}
+
+fn verify_backup(
+    client: &PgClient,
+    backup_path: &Path,
+    timeout: Option<Duration>,
+    shutdown_rx: &Receiver<()>,
+) -> Result<()> {
+    // WAL can only be parsed from plain format backups; the WAL archive is still checked against the manifest.
+    let mut process = client.program("pg_verifybackup");
+    process.arg("-n").arg(backup_path.as_os_str());
+
+    debug!("Final pg_verifybackup command: {:?}", redacted(&process));
+
+    let child = process
+        .spawn()
+        .context("Error while starting pg_verifybackup process.")?;
+
+    wait_for_child_success(child, timeout, shutdown_rx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::basebackup_option_args;
+    use crate::db::pgsql::config::{CompressionMethod, PostgresOptions};
+
+    #[test]
+    fn basebackup_option_args_given_defaults_streams_wal_into_gzipped_tar() {
+        assert_eq!(
+            basebackup_option_args(&PostgresOptions::default()),
+            vec!["-F", "t", "-X", "stream", "--compress=client-gzip"]
+        );
+    }
+
+    #[test]
+    fn basebackup_option_args_uses_configured_compression() {
+        let options = PostgresOptions {
+            compression: Some(CompressionMethod::Zstd),
+            compression_level: Some(3),
+            ..PostgresOptions::default()
+        };
+        assert_eq!(
+            basebackup_option_args(&options),
+            vec!["-F", "t", "-X", "stream", "--compress=client-zstd:3"]
+        );
+
+        let options = PostgresOptions {
+            compression: Some(CompressionMethod::None),
+            ..PostgresOptions::default()
+        };
+        assert_eq!(
+            basebackup_option_args(&options),
+            vec!["-F", "t", "-X", "stream"]
+        );
+    }
+}
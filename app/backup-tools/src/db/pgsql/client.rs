@@ -0,0 +1,341 @@
+use crate::common::output::{join_capture, read_stdout};
+use crate::common::process::{create_command, wait_for_child_success};
+use crate::common::redact::SecretCommandExt;
+use crate::db::pgsql::config::{PostgresConfig, PostgresOptions};
+use anyhow::{anyhow, bail, Context, Result};
+use crossbeam::channel::Receiver;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::{debug, info};
+
+/// Install directories of the PostgreSQL client programs on Alpine and Debian based images.
+pub const DEFAULT_CLIENT_DIRS: [&str; 2] = [
+    "/usr/libexec/postgresql{version}",
+    "/usr/lib/postgresql/{version}/bin",
+];
+const VERSION_PLACEHOLDER: &str = "{version}";
+/// How many major versions newer than the server are considered when no exactly matching client is installed.
+const NEWER_VERSION_SEARCH_LIMIT: u32 = 10;
+/// Database connected to when the target does not name one.
+const MAINTENANCE_DATABASE: &str = "postgres";
+
+/// The PostgreSQL client programs used to back up a server.
+///
+/// `pg_dump` and `pg_basebackup` refuse to back up a server with a newer major version than their own, so the programs
+/// are taken from the install directory matching the server's version when one exists.
+#[derive(Debug, Default, Clone)]
+pub struct PgClient {
+    bin_dir: Option<PathBuf>,
+    /// The major version of the client programs, if it was determined.
+    version: Option<u32>,
+}
+
+impl PgClient {
+    /// Finds client programs compatible with the server, preferring the same major version as the server, then newer
+    /// versions, and finally the programs on the `PATH`.
+    pub fn resolve(
+        config: &PostgresConfig,
+        options: &PostgresOptions,
+        shutdown_rx: &Receiver<()>,
+    ) -> Result<PgClient> {
+        let output = PgClient::default()
+            .run_query(config, "SHOW server_version_num", shutdown_rx)
+            .context("Failed to query the PostgreSQL server version.")?;
+        let server_version = parse_server_version_num(&output)?;
+        info!(server_version, "Detected PostgreSQL server major version.");
+
+        let program = options.backup_mode.unwrap_or_default().program();
+        let templates = client_dir_templates(options);
+
+        if let Some((bin_dir, version)) =
+            find_client_dir(server_version, &templates, |p| p.join(program).is_file())
+        {
+            info!(bin_dir=%bin_dir.display(), client_version = version, "Using PostgreSQL client programs.");
+            return Ok(PgClient {
+                bin_dir: Some(bin_dir),
+                version: Some(version),
+            });
+        }
+
+        let path_version = get_path_client_version(program, shutdown_rx)
+            .context("No PostgreSQL client was found in the configured install directories.")?;
+        if path_version < server_version {
+            bail!(
+                "No compatible {} found for PostgreSQL server version {}: the {} on the PATH is version {} \
+                and none of the install directories ({}) contain version {} or newer.",
+                program,
+                server_version,
+                program,
+                path_version,
+                templates.join(", "),
+                server_version
+            );
+        }
+
+        debug!(
+            client_version = path_version,
+            "Using PostgreSQL client programs on the PATH."
+        );
+        Ok(PgClient {
+            bin_dir: None,
+            version: Some(path_version),
+        })
+    }
+
+    /// Finds the newest installed client programs containing `program` that are at least the given major version.
+    pub fn find_newest(
+        program: &str,
+        min_version: u32,
+        options: &PostgresOptions,
+    ) -> Option<PgClient> {
+        let templates = client_dir_templates(options);
+        find_newest_client_dir(min_version, &templates, |p| p.join(program).is_file()).map(
+            |(bin_dir, version)| PgClient {
+                bin_dir: Some(bin_dir),
+                version: Some(version),
+            },
+        )
+    }
+
+    #[cfg(test)]
+    pub fn with_version(version: u32) -> PgClient {
+        PgClient {
+            bin_dir: None,
+            version: Some(version),
+        }
+    }
+
+    /// Whether the client programs are at least the given major version; unknown versions are assumed to be current.
+    pub fn is_at_least(&self, version: u32) -> bool {
+        self.version.is_none_or(|v| v >= version)
+    }
+
+    /// Creates a command for one of the client programs, connecting to the given server.
+    ///
+    /// The connection is configured through the libpq environment variables, which every client program reads and
+    /// which keep the password out of the process list.
+    pub fn command(&self, program: &str, config: &PostgresConfig) -> Command {
+        let mut process = self.program(program);
+
+        process
+            .env("PGHOST", &config.host)
+            .env("PGUSER", &config.username)
+            .secret_env("PGPASSWORD", &config.password);
+
+        let optional_vars = [
+            ("PGPORT", config.port.as_deref().map(OsStr::new)),
+            ("PGSSLMODE", config.ssl_mode.as_deref().map(OsStr::new)),
+            (
+                "PGSSLROOTCERT",
+                config.ssl_root_cert.as_deref().map(Path::as_os_str),
+            ),
+            ("PGSSLCERT", config.ssl_cert.as_deref().map(Path::as_os_str)),
+            ("PGSSLKEY", config.ssl_key.as_deref().map(Path::as_os_str)),
+            (
+                "PGAPPNAME",
+                config.application_name.as_deref().map(OsStr::new),
+            ),
+            ("PGOPTIONS", config.options.as_deref().map(OsStr::new)),
+        ];
+        for (key, value) in optional_vars {
+            if let Some(value) = value {
+                process.env(key, value);
+            }
+        }
+        process.envs(config.parameters.iter().map(|(key, value)| (key, value)));
+
+        process
+    }
+
+    /// Creates a command for one of the client programs without any connection settings.
+    pub fn program(&self, program: &str) -> Command {
+        match &self.bin_dir {
+            Some(dir) => create_command(dir.join(program)),
+            None => create_command(program),
+        }
+    }
+
+    /// Runs a query with `psql` and returns its unaligned output.
+    pub fn run_query(
+        &self,
+        config: &PostgresConfig,
+        query: &str,
+        shutdown_rx: &Receiver<()>,
+    ) -> Result<String> {
+        let mut child = self
+            .command("psql", config)
+            .args(["-d", maintenance_database(config)])
+            .arg("-w")
+            .arg("-At")
+            .args(["-c", query])
+            .spawn()
+            .context("Error while starting psql process.")?;
+
+        let output = read_stdout(&mut child)?;
+        let wait_result = wait_for_child_success(child, None, shutdown_rx);
+        let output = join_capture(output)?;
+        wait_result.context("psql query failed.")?;
+
+        Ok(output)
+    }
+}
+
+fn client_dir_templates(options: &PostgresOptions) -> Vec<&str> {
+    match &options.client_dirs {
+        Some(dirs) if !dirs.is_empty() => dirs.iter().map(String::as_str).collect(),
+        _ => DEFAULT_CLIENT_DIRS.to_vec(),
+    }
+}
+
+fn maintenance_database(config: &PostgresConfig) -> &str {
+    config
+        .database_name
+        .as_deref()
+        .filter(|db| !db.is_empty())
+        .unwrap_or(MAINTENANCE_DATABASE)
+}
+
+fn get_path_client_version(program: &str, shutdown_rx: &Receiver<()>) -> Result<u32> {
+    let mut child = create_command(program)
+        .arg("--version")
+        .spawn()
+        .with_context(|| format!("Error while starting {} process.", program))?;
+
+    let output = read_stdout(&mut child)?;
+    let wait_result = wait_for_child_success(child, None, shutdown_rx);
+    let output = join_capture(output)?;
+    wait_result?;
+
+    parse_client_version(&output)
+}
+
+/// Parses the output of `SHOW server_version_num`, e.g. `160002` for 16.2 or `90624` for 9.6.24.
+fn parse_server_version_num(output: &str) -> Result<u32> {
+    let version_num = output
+        .trim()
+        .parse::<u32>()
+        .with_context(|| format!("Unexpected PostgreSQL server version: {}", output.trim()))?;
+
+    // Before PostgreSQL 10, the major version consisted of the first two numbers.
+    if version_num >= 100000 {
+        Ok(version_num / 10000)
+    } else {
+        Ok(version_num / 10000 * 10 + version_num / 100 % 100)
+    }
+}
+
+/// Parses the output of `--version` of a client program, e.g. `pg_dump (PostgreSQL) 16.2`.
+fn parse_client_version(output: &str) -> Result<u32> {
+    output
+        .split_whitespace()
+        .last()
+        .and_then(|version| version.split('.').next())
+        .and_then(|major| major.parse::<u32>().ok())
+        .ok_or_else(|| anyhow!("Unexpected PostgreSQL client version: {}", output.trim()))
+}
+
+fn find_client_dir(
+    server_version: u32,
+    templates: &[&str],
+    has_client: impl Fn(&Path) -> bool,
+) -> Option<(PathBuf, u32)> {
+    (server_version..=server_version + NEWER_VERSION_SEARCH_LIMIT)
+        .find_map(|version| client_dir(version, templates, &has_client).map(|dir| (dir, version)))
+}
+
+fn find_newest_client_dir(
+    min_version: u32,
+    templates: &[&str],
+    has_client: impl Fn(&Path) -> bool,
+) -> Option<(PathBuf, u32)> {
+    (min_version..=min_version + NEWER_VERSION_SEARCH_LIMIT)
+        .rev()
+        .find_map(|version| client_dir(version, templates, &has_client).map(|dir| (dir, version)))
+}
+
+fn client_dir(
+    version: u32,
+    templates: &[&str],
+    has_client: impl Fn(&Path) -> bool,
+) -> Option<PathBuf> {
+    templates
+        .iter()
+        .map(|template| PathBuf::from(template.replace(VERSION_PLACEHOLDER, &version.to_string())))
+        .find(|dir| has_client(dir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        find_client_dir, find_newest_client_dir, parse_client_version, parse_server_version_num,
+        PgClient, DEFAULT_CLIENT_DIRS,
+    };
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn is_at_least_given_unknown_version_assumes_current() {
+        let client = PgClient {
+            bin_dir: None,
+            version: Some(17),
+        };
+
+        assert!(client.is_at_least(16));
+        assert!(!client.is_at_least(18));
+        assert!(PgClient::default().is_at_least(18));
+    }
+
+    #[test]
+    fn parse_server_version_num_handles_old_and_new_versioning() {
+        assert_eq!(parse_server_version_num("170004\n").unwrap(), 17);
+        assert_eq!(parse_server_version_num("100023").unwrap(), 10);
+        assert_eq!(parse_server_version_num("90624").unwrap(), 96);
+        assert!(parse_server_version_num("psql: error").is_err());
+    }
+
+    #[test]
+    fn parse_client_version_reads_major_version() {
+        assert_eq!(
+            parse_client_version("pg_dump (PostgreSQL) 16.2\n").unwrap(),
+            16
+        );
+        assert!(parse_client_version("").is_err());
+    }
+
+    #[test]
+    fn find_client_dir_prefers_matching_version() {
+        let installed = [
+            PathBuf::from("/usr/libexec/postgresql16"),
+            PathBuf::from("/usr/libexec/postgresql17"),
+        ];
+        let has_client = |p: &Path| installed.iter().any(|i| i == p);
+
+        assert_eq!(
+            find_client_dir(16, &DEFAULT_CLIENT_DIRS, has_client),
+            Some((PathBuf::from("/usr/libexec/postgresql16"), 16))
+        );
+        assert_eq!(
+            find_client_dir(15, &DEFAULT_CLIENT_DIRS, has_client),
+            Some((PathBuf::from("/usr/libexec/postgresql16"), 16))
+        );
+        assert_eq!(find_client_dir(18, &DEFAULT_CLIENT_DIRS, has_client), None);
+    }
+
+    #[test]
+    fn find_newest_client_dir_prefers_newest_version() {
+        let installed = [
+            PathBuf::from("/usr/libexec/postgresql18"),
+            PathBuf::from("/usr/lib/postgresql/19/bin"),
+        ];
+        let has_client = |p: &Path| installed.iter().any(|i| i == p);
+
+        assert_eq!(
+            find_newest_client_dir(18, &DEFAULT_CLIENT_DIRS, has_client),
+            Some((PathBuf::from("/usr/lib/postgresql/19/bin"), 19))
+        );
+        assert_eq!(
+            find_newest_client_dir(20, &DEFAULT_CLIENT_DIRS, has_client),
+            None
+        );
+    }
+}
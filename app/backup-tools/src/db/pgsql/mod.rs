@@ -1,4 +1,7 @@
 mod backup;
+mod basebackup;
+mod client;
 mod config;
+mod uri;
 
-pub use backup::backup_postgres;
+pub use backup::PostgresBackup;
@@ -0,0 +1,212 @@
+use crate::db::pgsql::config::PostgresConfig;
+use anyhow::{anyhow, bail, Context, Result};
+use percent_encoding::percent_decode_str;
+use tracing::warn;
+
+const SCHEMES: [&str; 2] = ["postgresql://", "postgres://"];
+/// The libpq connection parameters without a dedicated setting, with the environment variables the client programs
+/// read them from.
+const PARAMETER_ENV_VARS: [(&str, &str); 18] = [
+    ("hostaddr", "PGHOSTADDR"),
+    ("passfile", "PGPASSFILE"),
+    ("require_auth", "PGREQUIREAUTH"),
+    ("channel_binding", "PGCHANNELBINDING"),
+    ("connect_timeout", "PGCONNECT_TIMEOUT"),
+    ("client_encoding", "PGCLIENTENCODING"),
+    ("sslcompression", "PGSSLCOMPRESSION"),
+    ("sslcrl", "PGSSLCRL"),
+    ("sslcrldir", "PGSSLCRLDIR"),
+    ("sslnegotiation", "PGSSLNEGOTIATION"),
+    ("sslcertmode", "PGSSLCERTMODE"),
+    ("ssl_min_protocol_version", "PGSSLMINPROTOCOLVERSION"),
+    ("ssl_max_protocol_version", "PGSSLMAXPROTOCOLVERSION"),
+    ("gssencmode", "PGGSSENCMODE"),
+    ("krbsrvname", "PGKRBSRVNAME"),
+    ("gsslib", "PGGSSLIB"),
+    ("target_session_attrs", "PGTARGETSESSIONATTRS"),
+    ("load_balance_hosts", "PGLOADBALANCEHOSTS"),
+];
+
+/// Parses a libpq connection URI of the form
+/// `postgresql://[user[:password]@][host][:port][,...][/dbname][?param=value&...]`.
+///
+/// Unlike a generic URL parser, this accepts multiple hosts, percent-decodes every component so that credentials may
+/// contain reserved characters such as `@` or `%`, and reads the connection parameters given in the query string.
+pub fn parse_connection_uri(uri: &str) -> Result<PostgresConfig> {
+    let rest = SCHEMES
+        .iter()
+        .find_map(|scheme| uri.strip_prefix(scheme))
+        .ok_or_else(|| anyhow!("Connection URI must start with postgresql:// or postgres://."))?;
+
+    let (rest, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+    let (user_info, host_list) = match authority.rsplit_once('@') {
+        Some((user_info, host_list)) => (Some(user_info), host_list),
+        None => (None, authority),
+    };
+
+    let mut config = PostgresConfig::default();
+
+    if let Some(user_info) = user_info {
+        let (username, password) = user_info.split_once(':').unwrap_or((user_info, ""));
+        config.username = decode(username)?;
+        config.password = decode(password)?;
+    }
+
+    if !host_list.is_empty() {
+        let (hosts, ports) = parse_host_list(host_list)?;
+        config.host = hosts.join(",");
+        if ports.iter().any(|p| !p.is_empty()) {
+            config.port = Some(ports.join(","));
+        }
+    }
+
+    if !path.is_empty() {
+        config.database_name = Some(decode(path)?);
+    }
+
+    for pair in query.split('&').filter(|p| !p.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        apply_parameter(&mut config, &decode(key)?, decode(value)?)?;
+    }
+
+    if config.host.is_empty() {
+        bail!("No PGSQL host provided.");
+    }
+
+    Ok(config)
+}
+
+/// Splits `host[:port],...` into hosts and ports, keeping an empty port for hosts without one as libpq does.
+fn parse_host_list(host_list: &str) -> Result<(Vec<String>, Vec<String>)> {
+    let mut hosts = Vec::new();
+    let mut ports = Vec::new();
+
+    for entry in host_list.split(',') {
+        let (host, port) = if let Some(bracketed) = entry.strip_prefix('[') {
+            // IPv6 addresses are enclosed in brackets so that their colons are not mistaken for a port.
+            let (host, after) = bracketed
+                .split_once(']')
+                .ok_or_else(|| anyhow!("Unterminated IPv6 address in connection URI."))?;
+            (host, after.strip_prefix(':').unwrap_or(""))
+        } else {
+            entry.split_once(':').unwrap_or((entry, ""))
+        };
+
+        if !port.is_empty() {
+            port.parse::<u16>()
+                .with_context(|| format!("Invalid port in connection URI: {}", port))?;
+        }
+
+        hosts.push(decode(host)?);
+        ports.push(String::from(port));
+    }
+
+    Ok((hosts, ports))
+}
+
+fn apply_parameter(config: &mut PostgresConfig, key: &str, value: String) -> Result<()> {
+    match key {
+        "host" => config.host = value,
+        "port" => config.port = Some(value),
+        "user" => config.username = value,
+        "password" => config.password = value,
+        "dbname" => config.database_name = Some(value),
+        "sslmode" => config.ssl_mode = Some(value),
+        "sslrootcert" => config.ssl_root_cert = Some(value.into()),
+        "sslcert" => config.ssl_cert = Some(value.into()),
+        "sslkey" => config.ssl_key = Some(value.into()),
+        "application_name" => config.application_name = Some(value),
+        "options" => config.options = Some(value),
+        _ => match PARAMETER_ENV_VARS.iter().find(|(name, _)| *name == key) {
+            Some((_, env_var)) => config.parameters.push((String::from(*env_var), value)),
+            None => warn!(
+                parameter = key,
+                "Ignoring unknown connection URI parameter."
+            ),
+        },
+    }
+
+    Ok(())
+}
+
+fn decode(s: &str) -> Result<String> {
+    percent_decode_str(s)
+        .decode_utf8()
+        .map(|decoded| decoded.into_owned())
+        .with_context(|| format!("Connection URI component is not valid UTF-8: {}", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_connection_uri;
+    use std::path::PathBuf;
+
+    #[test]
+    fn parse_connection_uri_percent_decodes_credentials() {
+        let config =
+            parse_connection_uri("postgresql://app%40corp:p%40ss%25word@db/my%20db").unwrap();
+
+        assert_eq!(config.username, "app@corp");
+        assert_eq!(config.password, "p@ss%word");
+        assert_eq!(config.host, "db");
+        assert_eq!(config.database_name, Some(String::from("my db")));
+    }
+
+    #[test]
+    fn parse_connection_uri_given_multiple_hosts_joins_hosts_and_ports() {
+        let config =
+            parse_connection_uri("postgresql://user@primary:5433,[::1],replica:5434/app").unwrap();
+
+        assert_eq!(config.host, "primary,::1,replica");
+        assert_eq!(config.port, Some(String::from("5433,,5434")));
+    }
+
+    #[test]
+    fn parse_connection_uri_reads_tls_and_connection_parameters() {
+        let config = parse_connection_uri(
+            "postgres://user:pass@db/app?sslmode=verify-full&sslrootcert=%2Fcerts%2Fca.crt\
+            &sslcert=/certs/tls.crt&sslkey=/certs/tls.key&application_name=backup-tools\
+            &options=-c%20statement_timeout%3D0",
+        )
+        .unwrap();
+
+        assert_eq!(config.ssl_mode, Some(String::from("verify-full")));
+        assert_eq!(config.ssl_root_cert, Some(PathBuf::from("/certs/ca.crt")));
+        assert_eq!(config.ssl_cert, Some(PathBuf::from("/certs/tls.crt")));
+        assert_eq!(config.ssl_key, Some(PathBuf::from("/certs/tls.key")));
+        assert_eq!(config.application_name, Some(String::from("backup-tools")));
+        assert_eq!(config.options, Some(String::from("-c statement_timeout=0")));
+    }
+
+    #[test]
+    fn parse_connection_uri_given_host_parameter_allows_empty_authority() {
+        let config =
+            parse_connection_uri("postgresql:///app?host=%2Fvar%2Frun%2Fpostgresql").unwrap();
+        assert_eq!(config.host, "/var/run/postgresql");
+    }
+
+    #[test]
+    fn parse_connection_uri_passes_other_libpq_parameters_as_env_vars() {
+        let config = parse_connection_uri(
+            "postgresql://db/app?connect_timeout=10&target_session_attrs=read-write&foo=bar",
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.parameters,
+            vec![
+                (String::from("PGCONNECT_TIMEOUT"), String::from("10")),
+                (
+                    String::from("PGTARGETSESSIONATTRS"),
+                    String::from("read-write")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_connection_uri_given_invalid_port_returns_error() {
+        assert!(parse_connection_uri("postgresql://db:abc/app").is_err());
+    }
+}
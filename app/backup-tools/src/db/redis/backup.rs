@@ -0,0 +1,245 @@
+use crate::common::output::{join_capture, read_stdout};
+use crate::common::process::{create_command, wait_for_child_success};
+use crate::common::redact::{redacted, SecretCommandExt};
+use crate::db::database_backup::DatabaseBackup;
+use crate::db::redis::config::{
+    RedisBackupMode, RedisConfig, DEFAULT_REDIS_COMMAND, DEFAULT_REDIS_PORT, REDIS_PREFIX,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use crossbeam::channel::{after, Receiver};
+use crossbeam::select;
+use envy::prefixed;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, trace_span, warn};
+
+const DUMP_FILE_NAME: &str = "dump.rdb";
+const POLL_INTERVAL_SECS: u64 = 2;
+const DEFAULT_SAVE_TIMEOUT_SECS: u64 = 60 * 10; // 10 minutes
+
+pub struct RedisBackup;
+
+impl DatabaseBackup for RedisBackup {
+    fn name(&self) -> &str {
+        "Redis"
+    }
+
+    fn run_backup(
+        &self,
+        base_backup_path: &Path,
+        shutdown_rx: &Receiver<()>,
+    ) -> Result<Vec<PathBuf>> {
+        backup_redis(base_backup_path, shutdown_rx).map(|_| Vec::new())
+    }
+}
+
+fn backup_redis(base_backup_path: &Path, shutdown_rx: &Receiver<()>) -> Result<()> {
+    let span = trace_span!("redis");
+    let _entered = span.enter();
+
+    info!("Starting Redis backup.");
+    let config = prefixed(REDIS_PREFIX)
+        .from_env::<RedisConfig>()
+        .context("Error while mapping RedisConfig from individual env vars.")?;
+
+    let name = config.name.as_deref().unwrap_or(&config.host);
+    let backup_path = base_backup_path.join(format!("redis/{}", name));
+    fs::create_dir_all(&backup_path).context("Error while creating path to Redis backup.")?;
+    let dump_path = backup_path.join(DUMP_FILE_NAME);
+
+    match config.backup_mode.unwrap_or(RedisBackupMode::Rdb) {
+        RedisBackupMode::Rdb => dump_rdb(&config, &dump_path, shutdown_rx)?,
+        RedisBackupMode::Bgsave => save_and_copy_rdb(&config, &dump_path, shutdown_rx)?,
+    }
+
+    info!(path=%dump_path.display(), "Finished Redis backup.");
+    Ok(())
+}
+
+fn create_redis_cli(config: &RedisConfig) -> Command {
+    let program = config.command.as_deref().unwrap_or(DEFAULT_REDIS_COMMAND);
+    let port = config.port.unwrap_or(DEFAULT_REDIS_PORT);
+    let mut process = create_command(program);
+
+    process
+        .args(["-h", &config.host])
+        .args(["-p", &port.to_string()]);
+
+    if let Some(username) = &config.username {
+        process.args(["--user", username]);
+    }
+
+    // Passing the password through the environment keeps it out of the process list.
+    if let Some(password) = &config.password {
+        process.secret_env("REDISCLI_AUTH", password);
+    }
+
+    if config.tls.unwrap_or(false) {
+        process.arg("--tls");
+        if let Some(cacert) = &config.cacert {
+            process.arg("--cacert").arg(cacert.as_os_str());
+        }
+        if let Some(cert) = &config.cert {
+            process.arg("--cert").arg(cert.as_os_str());
+        }
+        if let Some(key) = &config.key {
+            process.arg("--key").arg(key.as_os_str());
+        }
+    }
+
+    process
+}
+
+fn dump_rdb(config: &RedisConfig, dump_path: &Path, shutdown_rx: &Receiver<()>) -> Result<()> {
+    // A leftover file from a previous run would hide a failed dump.
+    if dump_path.exists() {
+        fs::remove_file(dump_path).context("Error while removing previous Redis RDB file.")?;
+    }
+
+    let mut process = create_redis_cli(config);
+    process.arg("--rdb").arg(dump_path.as_os_str());
+    debug!("Final redis-cli command: {:?}", redacted(&process));
+
+    let child = process
+        .spawn()
+        .context("Error while starting redis-cli process.")?;
+    wait_for_child_success(child, config.timeout.map(Duration::from_secs), shutdown_rx)
+        .context("redis-cli failed to write an RDB file.")?;
+
+    let size = fs::metadata(dump_path).map(|m| m.len()).unwrap_or(0);
+    if size == 0 {
+        bail!(
+            "redis-cli did not write an RDB file to {}.",
+            dump_path.display()
+        );
+    }
+
+    Ok(())
+}
+
+fn save_and_copy_rdb(
+    config: &RedisConfig,
+    dump_path: &Path,
+    shutdown_rx: &Receiver<()>,
+) -> Result<()> {
+    let rdb_path = config
+        .rdb_path
+        .as_ref()
+        .ok_or_else(|| anyhow!("REDIS_RDB_PATH must be set when using the BGSAVE backup mode."))?;
+
+    let previous_save = parse_lastsave(&run_command(config, &["LASTSAVE"], shutdown_rx)?)?;
+    // SCHEDULE defers the save instead of failing if an AOF rewrite is in progress.
+    let reply = run_command(config, &["BGSAVE", "SCHEDULE"], shutdown_rx)?;
+    check_bgsave_reply(&reply)?;
+    info!("Triggered background save, waiting for it to complete.");
+
+    wait_for_save(config, previous_save, shutdown_rx)?;
+
+    fs::copy(rdb_path, dump_path).with_context(|| {
+        format!(
+            "Failed to copy the Redis RDB file from {}.",
+            rdb_path.display()
+        )
+    })?;
+
+    Ok(())
+}
+
+fn wait_for_save(
+    config: &RedisConfig,
+    previous_save: u64,
+    shutdown_rx: &Receiver<()>,
+) -> Result<()> {
+    let timeout = Duration::from_secs(config.timeout.unwrap_or(DEFAULT_SAVE_TIMEOUT_SECS));
+    let start = Instant::now();
+
+    loop {
+        select! {
+            recv(shutdown_rx) -> _ => {
+                warn!("Received notification to shutdown, no longer waiting for the background save.");
+                bail!("Stopped waiting for Redis background save due to shutdown.");
+            },
+            recv(after(Duration::from_secs(POLL_INTERVAL_SECS))) -> _ => {}
+        }
+
+        let last_save = parse_lastsave(&run_command(config, &["LASTSAVE"], shutdown_rx)?)?;
+        if last_save > previous_save {
+            debug!(last_save, "Background save completed.");
+            return Ok(());
+        }
+
+        if start.elapsed() > timeout {
+            bail!(
+                "Redis background save did not complete within {} seconds.",
+                timeout.as_secs()
+            );
+        }
+
+        debug!(
+            time_elapsed_seconds = start.elapsed().as_secs(),
+            "Background save still running."
+        );
+    }
+}
+
+fn run_command(config: &RedisConfig, args: &[&str], shutdown_rx: &Receiver<()>) -> Result<String> {
+    let mut child = create_redis_cli(config)
+        .args(args)
+        .spawn()
+        .context("Error while starting redis-cli process.")?;
+
+    let output = read_stdout(&mut child)?;
+    let wait_result = wait_for_child_success(child, None, shutdown_rx);
+    let output = join_capture(output)?;
+    wait_result.context("redis-cli command failed.")?;
+
+    Ok(output)
+}
+
+/// Parses the reply to `LASTSAVE`, which redis-cli prints as `(integer) <n>` on a terminal and `<n>` otherwise.
+fn parse_lastsave(reply: &str) -> Result<u64> {
+    let reply = reply.trim();
+    reply
+        .trim_start_matches("(integer)")
+        .trim()
+        .parse::<u64>()
+        .with_context(|| format!("Unexpected reply to LASTSAVE: {}", reply))
+}
+
+fn check_bgsave_reply(reply: &str) -> Result<()> {
+    let reply = reply.trim();
+    if reply.starts_with("Background saving") {
+        Ok(())
+    } else {
+        bail!("Redis refused to start a background save: {}", reply)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_bgsave_reply, parse_lastsave};
+
+    #[test]
+    fn parse_lastsave_given_raw_reply_parses() {
+        assert_eq!(parse_lastsave("1718000000\n").unwrap(), 1718000000);
+    }
+
+    #[test]
+    fn parse_lastsave_given_formatted_reply_parses() {
+        assert_eq!(parse_lastsave("(integer) 1718000000").unwrap(), 1718000000);
+    }
+
+    #[test]
+    fn parse_lastsave_given_error_returns_error() {
+        assert!(parse_lastsave("NOAUTH Authentication required.").is_err());
+    }
+
+    #[test]
+    fn check_bgsave_reply_accepts_started_and_scheduled() {
+        assert!(check_bgsave_reply("Background saving started\n").is_ok());
+        assert!(check_bgsave_reply("Background saving scheduled").is_ok());
+        assert!(check_bgsave_reply("ERR Background save already in progress").is_err());
+    }
+}
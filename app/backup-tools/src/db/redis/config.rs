@@ -0,0 +1,56 @@
+use serde::Deserialize;
+use std::path::PathBuf;
+
+pub const REDIS_PREFIX: &str = "REDIS_";
+pub const DEFAULT_REDIS_PORT: u16 = 6379;
+pub const DEFAULT_REDIS_COMMAND: &str = "redis-cli";
+
+#[derive(Debug, Deserialize)]
+pub struct RedisConfig {
+    pub host: String,
+    pub port: Option<u16>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Name of the directory under `db/redis` the RDB file is written to; defaults to the host.
+    pub name: Option<String>,
+    pub tls: Option<bool>,
+    pub cacert: Option<PathBuf>,
+    pub cert: Option<PathBuf>,
+    pub key: Option<PathBuf>,
+    pub backup_mode: Option<RedisBackupMode>,
+    /// Path to the RDB file written by the server, which must be readable by backup-tools when using `BGSAVE`.
+    pub rdb_path: Option<PathBuf>,
+    pub command: Option<String>,
+    pub timeout: Option<u64>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum RedisBackupMode {
+    /// Streams an RDB file from the server over a replication connection with `redis-cli --rdb`.
+    Rdb,
+    /// Triggers `BGSAVE` on the server and copies the RDB file it writes once `LASTSAVE` changes.
+    Bgsave,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RedisBackupMode;
+    use serde::de::IntoDeserializer;
+    use serde::Deserialize;
+
+    fn deserialize(s: &str) -> Result<RedisBackupMode, serde::de::value::Error> {
+        RedisBackupMode::deserialize(s.into_deserializer())
+    }
+
+    #[test]
+    fn deserialize_modes() {
+        assert_eq!(deserialize("RDB").unwrap(), RedisBackupMode::Rdb);
+        assert_eq!(deserialize("BGSAVE").unwrap(), RedisBackupMode::Bgsave);
+    }
+
+    #[test]
+    fn deserialize_unknown_variant_returns_error() {
+        assert!(deserialize("SAVE").is_err());
+    }
+}
@@ -0,0 +1,358 @@
+use crate::common::output::{join_capture, read_stdout};
+use crate::common::process::{create_command, wait_for_child_success};
+use crate::common::redact::redacted;
+use crate::db::database_backup::DatabaseBackup;
+use crate::db::sqlite::config::{SqliteConfig, DEFAULT_SQLITE_COMMAND, SQLITE_PREFIX};
+use anyhow::{bail, Context, Result};
+use crossbeam::channel::Receiver;
+use envy::prefixed;
+use std::fs;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{debug, info, trace_span, warn};
+
+/// Every SQLite database file starts with this 16 byte header.
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+/// Files SQLite keeps next to a database while it is in use.
+const SIDECAR_SUFFIXES: [&str; 3] = ["-wal", "-shm", "-journal"];
+const BUSY_TIMEOUT_MILLIS: u32 = 10_000;
+
+pub struct SqliteBackup<'a> {
+    source_path: &'a Path,
+}
+
+impl<'a> SqliteBackup<'a> {
+    pub fn new(source_path: &'a Path) -> Self {
+        SqliteBackup { source_path }
+    }
+}
+
+impl DatabaseBackup for SqliteBackup<'_> {
+    fn name(&self) -> &str {
+        "SQLite"
+    }
+
+    fn run_backup(
+        &self,
+        base_backup_path: &Path,
+        shutdown_rx: &Receiver<()>,
+    ) -> Result<Vec<PathBuf>> {
+        backup_sqlite(self.source_path, base_backup_path, shutdown_rx)
+    }
+}
+
+/// Snapshots SQLite databases into `<base_backup_path>/sqlite` using the online backup API of the `sqlite3` shell and
+/// verifies each snapshot with `PRAGMA integrity_check`.
+///
+/// Returns the paths, relative to the source path, of the live database files that should be excluded from the file
+/// backup. This is empty unless `SQLITE_EXCLUDE_ORIGINALS` is enabled.
+fn backup_sqlite(
+    source_path: &Path,
+    base_backup_path: &Path,
+    shutdown_rx: &Receiver<()>,
+) -> Result<Vec<PathBuf>> {
+    let span = trace_span!("sqlite");
+    let _entered = span.enter();
+
+    info!("Starting SQLite backup.");
+    let config = prefixed(SQLITE_PREFIX)
+        .from_env::<SqliteConfig>()
+        .context("Error while loading SQLite config.")?;
+
+    let databases = find_databases(&config, source_path, base_backup_path)?;
+    if databases.is_empty() {
+        warn!("No SQLite databases were configured or discovered.");
+        return Ok(Vec::new());
+    }
+
+    for database in &databases {
+        let snapshot = snapshot_path(source_path, base_backup_path, database);
+        info!(database=%database.display(), "Snapshotting SQLite database.");
+        snapshot_database(&config, database, &snapshot, shutdown_rx)?;
+        verify_integrity(&config, &snapshot, shutdown_rx).with_context(|| {
+            format!(
+                "Integrity check failed for snapshot of {}.",
+                database.display()
+            )
+        })?;
+    }
+
+    if config.exclude_originals.unwrap_or(false) {
+        Ok(original_excludes(source_path, &databases))
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+fn find_databases(
+    config: &SqliteConfig,
+    source_path: &Path,
+    base_backup_path: &Path,
+) -> Result<Vec<PathBuf>> {
+    let mut databases: Vec<PathBuf> = config
+        .database_paths
+        .iter()
+        .flatten()
+        .map(|p| source_path.join(p))
+        .collect();
+
+    if config.discover.unwrap_or(false) {
+        discover_databases(source_path, base_backup_path, &mut databases)
+            .context("Error while searching for SQLite databases.")?;
+    }
+
+    databases.sort();
+    databases.dedup();
+    Ok(databases)
+}
+
+/// Recursively searches a directory for SQLite databases, skipping the database backup directory so that previous
+/// snapshots are not picked up again.
+fn discover_databases(dir: &Path, skip: &Path, found: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+
+        if file_type.is_dir() && path != skip {
+            discover_databases(&path, skip, found).unwrap_or_else(|e| {
+                warn!(ex=?e, path=%path.display(), "Skipping directory that could not be read.")
+            });
+        } else if file_type.is_file() {
+            match is_sqlite_database(&path) {
+                Ok(true) => {
+                    debug!(path=%path.display(), "Discovered SQLite database.");
+                    found.push(path);
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    warn!(ex=?e, path=%path.display(), "Skipping file that could not be read.")
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn is_sqlite_database(path: &Path) -> Result<bool> {
+    let mut header = [0u8; SQLITE_HEADER.len()];
+    let mut file = File::open(path)?;
+    match file.read_exact(&mut header) {
+        Ok(_) => Ok(&header == SQLITE_HEADER),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Snapshots keep their location relative to the source path; databases outside of it are stored by file name.
+fn snapshot_path(source_path: &Path, base_backup_path: &Path, database: &Path) -> PathBuf {
+    let relative = database
+        .strip_prefix(source_path)
+        .ok()
+        .map(PathBuf::from)
+        .or_else(|| database.file_name().map(PathBuf::from))
+        .unwrap_or_else(|| PathBuf::from("database.sqlite"));
+
+    base_backup_path.join("sqlite").join(relative)
+}
+
+fn snapshot_database(
+    config: &SqliteConfig,
+    database: &Path,
+    snapshot: &Path,
+    shutdown_rx: &Receiver<()>,
+) -> Result<()> {
+    if let Some(parent) = snapshot.parent() {
+        fs::create_dir_all(parent).context("Error while creating path to SQLite snapshot.")?;
+    }
+    if snapshot.exists() {
+        fs::remove_file(snapshot).context("Error while removing previous SQLite snapshot.")?;
+    }
+
+    let program = config.command.as_deref().unwrap_or(DEFAULT_SQLITE_COMMAND);
+    let mut process = create_command(program);
+    process
+        .arg("-bail")
+        .args(["-cmd", &format!(".timeout {}", BUSY_TIMEOUT_MILLIS)])
+        .arg(database.as_os_str())
+        .arg(format!(".backup {}", quote_dot_command_arg(snapshot)));
+
+    debug!("Final {} command: {:?}", program, redacted(&process));
+
+    let child = process
+        .spawn()
+        .with_context(|| format!("Error while starting {} process.", program))?;
+    wait_for_child_success(child, config.timeout.map(Duration::from_secs), shutdown_rx)
+        .with_context(|| format!("{} failed to snapshot {}.", program, database.display()))?;
+
+    if !snapshot.is_file() {
+        bail!(
+            "{} did not create a snapshot of {}.",
+            program,
+            database.display()
+        );
+    }
+
+    Ok(())
+}
+
+fn verify_integrity(
+    config: &SqliteConfig,
+    snapshot: &Path,
+    shutdown_rx: &Receiver<()>,
+) -> Result<()> {
+    let program = config.command.as_deref().unwrap_or(DEFAULT_SQLITE_COMMAND);
+    let mut child = create_command(program)
+        .arg("-readonly")
+        .arg(snapshot.as_os_str())
+        .arg("PRAGMA integrity_check;")
+        .spawn()
+        .with_context(|| format!("Error while starting {} process.", program))?;
+
+    let output = read_stdout(&mut child)?;
+    let wait_result =
+        wait_for_child_success(child, config.timeout.map(Duration::from_secs), shutdown_rx);
+    let output = join_capture(output)?;
+    wait_result.context("SQLite integrity check failed.")?;
+
+    if output.trim() != "ok" {
+        bail!("PRAGMA integrity_check reported: {}", output.trim());
+    }
+
+    debug!(snapshot=%snapshot.display(), "SQLite snapshot passed integrity check.");
+    Ok(())
+}
+
+/// Quotes an argument for a dot-command in the `sqlite3` shell, which unescapes backslashes within double quotes.
+fn quote_dot_command_arg(path: &Path) -> String {
+    let escaped = path
+        .to_string_lossy()
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"");
+    format!("\"{}\"", escaped)
+}
+
+fn original_excludes(source_path: &Path, databases: &[PathBuf]) -> Vec<PathBuf> {
+    databases
+        .iter()
+        .filter_map(|database| database.strip_prefix(source_path).ok())
+        .flat_map(|relative| {
+            let mut paths = vec![relative.to_path_buf()];
+            paths.extend(SIDECAR_SUFFIXES.iter().map(|suffix| {
+                let mut name = relative.as_os_str().to_os_string();
+                name.push(suffix);
+                PathBuf::from(name)
+            }));
+            paths
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        find_databases, is_sqlite_database, original_excludes, quote_dot_command_arg,
+        snapshot_path, SQLITE_HEADER,
+    };
+    use crate::db::sqlite::config::SqliteConfig;
+    use std::env::temp_dir;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    fn make_test_dir(name: &str) -> PathBuf {
+        let path = temp_dir().join(format!("backup_tools_sqlite_{}", name));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).expect("Failed to create test directory");
+        path
+    }
+
+    fn write_database(path: &Path) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let mut contents = SQLITE_HEADER.to_vec();
+        contents.extend_from_slice(&[0u8; 84]);
+        fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn is_sqlite_database_checks_header() {
+        let dir = make_test_dir("header");
+        write_database(&dir.join("app.db"));
+        fs::write(dir.join("notes.txt"), b"SQLite").unwrap();
+
+        assert!(is_sqlite_database(&dir.join("app.db")).unwrap());
+        assert!(!is_sqlite_database(&dir.join("notes.txt")).unwrap());
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn find_databases_given_discover_skips_backup_directory() {
+        let dir = make_test_dir("discover");
+        write_database(&dir.join("vault/db.sqlite3"));
+        write_database(&dir.join("db/sqlite/vault/db.sqlite3"));
+        fs::write(dir.join("vault/config.json"), b"{}").unwrap();
+        let config = SqliteConfig {
+            database_paths: Some(vec![PathBuf::from("vault/db.sqlite3")]),
+            discover: Some(true),
+            ..SqliteConfig::default()
+        };
+
+        let result = find_databases(&config, &dir, &dir.join("db")).unwrap();
+
+        assert_eq!(result, vec![dir.join("vault/db.sqlite3")]);
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn snapshot_path_keeps_relative_location() {
+        let actual = snapshot_path(
+            Path::new("/data"),
+            Path::new("/data/db"),
+            Path::new("/data/hass/home-assistant_v2.db"),
+        );
+        assert_eq!(
+            actual,
+            PathBuf::from("/data/db/sqlite/hass/home-assistant_v2.db")
+        );
+    }
+
+    #[test]
+    fn snapshot_path_given_database_outside_source_uses_file_name() {
+        let actual = snapshot_path(
+            Path::new("/data"),
+            Path::new("/data/db"),
+            Path::new("/var/lib/app/app.db"),
+        );
+        assert_eq!(actual, PathBuf::from("/data/db/sqlite/app.db"));
+    }
+
+    #[test]
+    fn original_excludes_includes_sidecar_files_within_source() {
+        let actual = original_excludes(
+            Path::new("/data"),
+            &[
+                PathBuf::from("/data/vault/db.sqlite3"),
+                PathBuf::from("/elsewhere/app.db"),
+            ],
+        );
+        assert_eq!(
+            actual,
+            vec![
+                PathBuf::from("vault/db.sqlite3"),
+                PathBuf::from("vault/db.sqlite3-wal"),
+                PathBuf::from("vault/db.sqlite3-shm"),
+                PathBuf::from("vault/db.sqlite3-journal"),
+            ]
+        );
+    }
+
+    #[test]
+    fn quote_dot_command_arg_escapes_quotes_and_backslashes() {
+        assert_eq!(
+            quote_dot_command_arg(Path::new(r#"/data/my "db"\x"#)),
+            r#""/data/my \"db\"\\x""#
+        );
+    }
+}
@@ -0,0 +1,17 @@
+use serde::Deserialize;
+use std::path::PathBuf;
+
+pub const SQLITE_PREFIX: &str = "SQLITE_";
+pub const DEFAULT_SQLITE_COMMAND: &str = "sqlite3";
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SqliteConfig {
+    /// Paths to databases, either absolute or relative to the source path.
+    pub database_paths: Option<Vec<PathBuf>>,
+    /// Whether to search the source path for databases by their file header.
+    pub discover: Option<bool>,
+    /// Whether the live database files should be left out of the file backup in favor of their snapshots.
+    pub exclude_originals: Option<bool>,
+    pub command: Option<String>,
+    pub timeout: Option<u64>,
+}
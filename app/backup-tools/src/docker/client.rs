@@ -0,0 +1,280 @@
+use crate::docker::config::DEFAULT_SOCKET_PATH;
+use crate::docker::model::ContainerSummary;
+use crate::docker::DockerConfig;
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use tracing::debug;
+use url::form_urlencoded;
+
+pub trait DockerClient {
+    fn list_running_containers(&self, filters: &ContainerFilters) -> Result<Vec<ContainerSummary>>;
+
+    fn stop(&self, id: &str, timeout_secs: Option<u64>) -> Result<()>;
+
+    fn start(&self, id: &str) -> Result<()>;
+
+    fn pause(&self, id: &str) -> Result<()>;
+
+    fn unpause(&self, id: &str) -> Result<()>;
+}
+
+/// Filters for listing containers, serialized in the JSON form expected by the `filters` query parameter.
+#[derive(Debug, Default, Serialize)]
+pub struct ContainerFilters {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    name: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    label: Vec<String>,
+}
+
+impl ContainerFilters {
+    /// Creates separate filters for the container names and the compose project, since the daemon only lists
+    /// containers matching every filter of a request while containers matching either are wanted.
+    pub fn for_config(config: &DockerConfig) -> Vec<ContainerFilters> {
+        let by_name = ContainerFilters {
+            // The name filter is a regular expression matched against names that start with a slash.
+            name: config
+                .container_names
+                .iter()
+                .flatten()
+                .map(|n| format!("^/{}$", escape_regex(n)))
+                .collect(),
+            ..ContainerFilters::default()
+        };
+        let by_label = ContainerFilters {
+            label: config
+                .compose_project
+                .iter()
+                .map(|p| format!("com.docker.compose.project={}", p))
+                .collect(),
+            ..ContainerFilters::default()
+        };
+
+        [by_name, by_label]
+            .into_iter()
+            .filter(|f| !f.is_empty())
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.name.is_empty() && self.label.is_empty()
+    }
+}
+
+fn escape_regex(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if "\\.+*?()|[]{}^$".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// A minimal HTTP client for the Docker Engine API over its Unix socket.
+///
+/// Requests are made with HTTP/1.0 so that the daemon closes the connection after each response and does not use a
+/// chunked transfer encoding, which keeps response handling to reading the stream until it is closed.
+pub struct DefaultDockerClient {
+    socket_path: PathBuf,
+}
+
+impl DefaultDockerClient {
+    pub fn new(config: &DockerConfig) -> DefaultDockerClient {
+        DefaultDockerClient {
+            socket_path: config
+                .socket_path
+                .clone()
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_SOCKET_PATH)),
+        }
+    }
+
+    fn request(&self, method: &str, path: &str) -> Result<DockerResponse> {
+        debug!("Docker Client Begin: {} {}", method, path);
+        let mut stream = UnixStream::connect(&self.socket_path).with_context(|| {
+            format!(
+                "Failed to connect to the Docker socket at {}.",
+                self.socket_path.display()
+            )
+        })?;
+
+        write!(
+            stream,
+            "{} {} HTTP/1.0\r\nHost: docker\r\nContent-Length: 0\r\n\r\n",
+            method, path
+        )?;
+
+        let mut raw = Vec::new();
+        stream
+            .read_to_end(&mut raw)
+            .context("Failed to read response from the Docker socket.")?;
+
+        let response = parse_response(&raw)?;
+        debug!(
+            "Docker Client End: {} {} - {}",
+            method, path, response.status
+        );
+
+        Ok(response)
+    }
+
+    fn post_container_action(&self, id: &str, action: &str, query: Option<&str>) -> Result<()> {
+        let path = match query {
+            Some(q) => format!("/containers/{}/{}?{}", id, action, q),
+            None => format!("/containers/{}/{}", id, action),
+        };
+
+        self.request("POST", &path)?.ensure_success(&path)
+    }
+}
+
+impl DockerClient for DefaultDockerClient {
+    fn list_running_containers(&self, filters: &ContainerFilters) -> Result<Vec<ContainerSummary>> {
+        let query: String = form_urlencoded::Serializer::new(String::new())
+            .append_pair("filters", &serde_json::to_string(filters)?)
+            .finish();
+        let path = format!("/containers/json?{}", query);
+
+        let response = self.request("GET", &path)?;
+        response.ensure_success(&path)?;
+
+        serde_json::from_slice(&response.body).context("Failed to parse the Docker container list.")
+    }
+
+    fn stop(&self, id: &str, timeout_secs: Option<u64>) -> Result<()> {
+        let query = timeout_secs.map(|t| format!("t={}", t));
+        self.post_container_action(id, "stop", query.as_deref())
+    }
+
+    fn start(&self, id: &str) -> Result<()> {
+        self.post_container_action(id, "start", None)
+    }
+
+    fn pause(&self, id: &str) -> Result<()> {
+        self.post_container_action(id, "pause", None)
+    }
+
+    fn unpause(&self, id: &str) -> Result<()> {
+        self.post_container_action(id, "unpause", None)
+    }
+}
+
+#[derive(Debug)]
+struct DockerResponse {
+    status: u16,
+    body: Vec<u8>,
+}
+
+impl DockerResponse {
+    fn ensure_success(&self, path: &str) -> Result<()> {
+        // 304 Not Modified is returned when a container is already in the requested state.
+        if (200..300).contains(&self.status) || self.status == 304 {
+            return Ok(());
+        }
+
+        let message = serde_json::from_slice::<HashMap<String, String>>(&self.body)
+            .ok()
+            .and_then(|m| m.get("message").cloned())
+            .unwrap_or_else(|| String::from_utf8_lossy(&self.body).into_owned());
+
+        bail!(
+            "Docker request to {} failed with status {}: {}",
+            path,
+            self.status,
+            message
+        )
+    }
+}
+
+fn parse_response(raw: &[u8]) -> Result<DockerResponse> {
+    let header_end = raw
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .ok_or_else(|| anyhow!("Malformed response from the Docker socket."))?;
+    let head = String::from_utf8_lossy(&raw[..header_end]);
+    let status = head
+        .lines()
+        .next()
+        .and_then(|status_line| status_line.split_whitespace().nth(1))
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| anyhow!("Missing status code in response from the Docker socket."))?;
+
+    Ok(DockerResponse {
+        status,
+        body: raw[header_end + 4..].to_vec(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_response, ContainerFilters};
+    use crate::docker::DockerConfig;
+
+    fn config(names: Option<Vec<&str>>, project: Option<&str>) -> DockerConfig {
+        DockerConfig {
+            socket_path: None,
+            container_names: names.map(|n| n.into_iter().map(String::from).collect()),
+            compose_project: project.map(String::from),
+            quiesce_mode: None,
+            stop_timeout: None,
+        }
+    }
+
+    #[test]
+    fn container_filters_given_names_and_project_creates_separate_filters() {
+        let filters =
+            ContainerFilters::for_config(&config(Some(vec!["web", "worker.1"]), Some("app")));
+        let json: Vec<String> = filters
+            .iter()
+            .map(|f| serde_json::to_string(f).unwrap())
+            .collect();
+        assert_eq!(
+            json,
+            vec![
+                r#"{"name":["^/web$","^/worker\\.1$"]}"#,
+                r#"{"label":["com.docker.compose.project=app"]}"#
+            ]
+        );
+    }
+
+    #[test]
+    fn container_filters_given_nothing_is_empty() {
+        assert!(ContainerFilters::for_config(&config(None, None)).is_empty());
+    }
+
+    #[test]
+    fn parse_response_reads_status_and_body() {
+        let raw = b"HTTP/1.0 200 OK\r\nContent-Type: application/json\r\n\r\n[]";
+        let response = parse_response(raw).unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, b"[]");
+    }
+
+    #[test]
+    fn ensure_success_given_not_modified_succeeds() {
+        let response = parse_response(b"HTTP/1.0 304 Not Modified\r\n\r\n").unwrap();
+        assert!(response.ensure_success("/containers/abc/stop").is_ok());
+    }
+
+    #[test]
+    fn ensure_success_given_error_includes_daemon_message() {
+        let raw = b"HTTP/1.0 404 Not Found\r\n\r\n{\"message\":\"No such container: abc\"}";
+        let response = parse_response(raw).unwrap();
+        let result = response.ensure_success("/containers/abc/stop");
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("No such container"));
+    }
+
+    #[test]
+    fn parse_response_given_malformed_response_returns_error() {
+        assert!(parse_response(b"garbage").is_err());
+    }
+}
@@ -0,0 +1,49 @@
+use serde::Deserialize;
+use std::path::PathBuf;
+
+pub const DOCKER_PREFIX: &str = "DOCKER_";
+pub const DEFAULT_SOCKET_PATH: &str = "/var/run/docker.sock";
+
+#[derive(Debug, Deserialize)]
+pub struct DockerConfig {
+    pub socket_path: Option<PathBuf>,
+    pub container_names: Option<Vec<String>>,
+    pub compose_project: Option<String>,
+    pub quiesce_mode: Option<QuiesceMode>,
+    pub stop_timeout: Option<u64>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum QuiesceMode {
+    Stop,
+    Pause,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::QuiesceMode;
+    use serde::de::IntoDeserializer;
+    use serde::Deserialize;
+
+    fn deserialize(s: &str) -> Result<QuiesceMode, serde::de::value::Error> {
+        QuiesceMode::deserialize(s.into_deserializer())
+    }
+
+    #[test]
+    fn deserialize_stop() {
+        assert!(matches!(deserialize("STOP").unwrap(), QuiesceMode::Stop));
+    }
+
+    #[test]
+    fn deserialize_pause() {
+        assert!(matches!(deserialize("PAUSE").unwrap(), QuiesceMode::Pause));
+    }
+
+    #[test]
+    fn deserialize_unknown_variant_returns_error() {
+        assert!(deserialize("stop").is_err());
+        assert!(deserialize("KILL").is_err());
+        assert!(deserialize("").is_err());
+    }
+}
@@ -0,0 +1,7 @@
+mod client;
+mod config;
+mod model;
+pub mod quiesce;
+
+use client::{DefaultDockerClient, DockerClient};
+use config::DockerConfig;
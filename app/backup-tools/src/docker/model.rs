@@ -0,0 +1,40 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ContainerSummary {
+    pub id: String,
+    #[serde(default)]
+    pub names: Vec<String>,
+}
+
+impl ContainerSummary {
+    /// Container names are reported with a leading slash by the Docker Engine API.
+    pub fn name(&self) -> &str {
+        self.names
+            .first()
+            .map(|n| n.trim_start_matches('/'))
+            .unwrap_or(&self.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ContainerSummary;
+
+    #[test]
+    fn deserialize_container_list() {
+        let json = r#"[{"Id":"abc123","Names":["/app-web-1"],"State":"running"}]"#;
+        let containers: Vec<ContainerSummary> = serde_json::from_str(json).unwrap();
+        assert_eq!(containers.len(), 1);
+        assert_eq!(containers[0].id, "abc123");
+        assert_eq!(containers[0].name(), "app-web-1");
+    }
+
+    #[test]
+    fn name_given_no_names_falls_back_to_id() {
+        let json = r#"{"Id":"abc123"}"#;
+        let container: ContainerSummary = serde_json::from_str(json).unwrap();
+        assert_eq!(container.name(), "abc123");
+    }
+}
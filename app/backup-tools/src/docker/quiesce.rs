@@ -0,0 +1,287 @@
+use crate::docker::client::ContainerFilters;
+use crate::docker::config::{QuiesceMode, DOCKER_PREFIX};
+use crate::docker::model::ContainerSummary;
+use crate::docker::{DefaultDockerClient, DockerClient, DockerConfig};
+use anyhow::{bail, Context, Result};
+use envy::prefixed;
+use tracing::{error, info, trace_span};
+
+pub fn quiesce_containers(inner: impl FnOnce() -> Result<()>) -> Result<()> {
+    let span = trace_span!("docker");
+    let _entered = span.enter();
+
+    let docker_config = prefixed(DOCKER_PREFIX)
+        .from_env::<DockerConfig>()
+        .context("Error while loading Docker config.")?;
+    let docker_client = DefaultDockerClient::new(&docker_config);
+
+    run_with_quiesced_containers(&docker_client, &docker_config, inner)
+}
+
+fn run_with_quiesced_containers(
+    client: &impl DockerClient,
+    config: &DockerConfig,
+    inner: impl FnOnce() -> Result<()>,
+) -> Result<()> {
+    let filters = ContainerFilters::for_config(config);
+    if filters.is_empty() {
+        bail!("No container names or compose project configured for Docker quiescing.");
+    }
+
+    let mode = config.quiesce_mode.unwrap_or(QuiesceMode::Stop);
+    let containers =
+        list_matching_containers(client, &filters).context("Retrieving running containers.")?;
+
+    if containers.is_empty() {
+        info!("No matching containers are running, no quiescing needed.")
+    } else {
+        info!(count = containers.len(), mode=?mode, "Quiescing containers...");
+    }
+
+    let mut quiesced: Vec<&ContainerSummary> = Vec::new();
+    for container in &containers {
+        if let Err(e) = quiesce(client, config, mode, container) {
+            error!("Failed to quiesce containers; attempting to resume the containers already quiesced.");
+            resume_all(client, mode, &quiesced);
+            return Err(e.context(format!("Failed to quiesce container {}.", container.name())));
+        }
+
+        info!(container = container.name(), "Quiesced container.");
+        quiesced.push(container);
+    }
+
+    let inner_result = inner();
+    if inner_result.is_err() {
+        error!("Executing inner backup process failed! Attempting to resume containers anyway.");
+    }
+
+    resume_all(client, mode, &quiesced);
+
+    inner_result
+}
+
+/// Lists the running containers matching any of the filters, each once.
+fn list_matching_containers(
+    client: &impl DockerClient,
+    filters: &[ContainerFilters],
+) -> Result<Vec<ContainerSummary>> {
+    let mut containers: Vec<ContainerSummary> = Vec::new();
+    for filter in filters {
+        for container in client.list_running_containers(filter)? {
+            if !containers.iter().any(|c| c.id == container.id) {
+                containers.push(container);
+            }
+        }
+    }
+
+    Ok(containers)
+}
+
+fn quiesce(
+    client: &impl DockerClient,
+    config: &DockerConfig,
+    mode: QuiesceMode,
+    container: &ContainerSummary,
+) -> Result<()> {
+    match mode {
+        QuiesceMode::Stop => client.stop(&container.id, config.stop_timeout),
+        QuiesceMode::Pause => client.pause(&container.id),
+    }
+}
+
+fn resume_all(client: &impl DockerClient, mode: QuiesceMode, containers: &[&ContainerSummary]) {
+    // Containers are resumed in the reverse order they were quiesced in.
+    for container in containers.iter().rev() {
+        let result = match mode {
+            QuiesceMode::Stop => client.start(&container.id),
+            QuiesceMode::Pause => client.unpause(&container.id),
+        };
+
+        match result {
+            Ok(_) => info!(container = container.name(), "Resumed container."),
+            Err(e) => error!(ex=?e, container = container.name(), "Failed to resume container."),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::run_with_quiesced_containers;
+    use crate::docker::client::ContainerFilters;
+    use crate::docker::config::QuiesceMode;
+    use crate::docker::model::ContainerSummary;
+    use crate::docker::{DockerClient, DockerConfig};
+    use anyhow::{anyhow, bail, Result};
+    use std::cell::RefCell;
+
+    struct MockDockerClient {
+        containers: Vec<&'static str>,
+        /// The containers listed for a filter by name instead of by compose project.
+        named_containers: Vec<&'static str>,
+        fail_stop_for: Option<&'static str>,
+        calls: RefCell<Vec<String>>,
+    }
+
+    impl MockDockerClient {
+        fn new(containers: Vec<&'static str>) -> Self {
+            MockDockerClient {
+                containers,
+                named_containers: Vec::new(),
+                fail_stop_for: None,
+                calls: RefCell::new(Vec::new()),
+            }
+        }
+
+        fn calls(&self) -> Vec<String> {
+            self.calls.borrow().clone()
+        }
+
+        fn record(&self, action: &str, id: &str) {
+            self.calls.borrow_mut().push(format!("{} {}", action, id));
+        }
+    }
+
+    impl DockerClient for MockDockerClient {
+        fn list_running_containers(
+            &self,
+            filters: &ContainerFilters,
+        ) -> Result<Vec<ContainerSummary>> {
+            let by_name = serde_json::to_value(filters).unwrap().get("name").is_some();
+            let containers = if by_name {
+                &self.named_containers
+            } else {
+                &self.containers
+            };
+            Ok(containers
+                .iter()
+                .map(|id| ContainerSummary {
+                    id: id.to_string(),
+                    names: vec![format!("/{}", id)],
+                })
+                .collect())
+        }
+
+        fn stop(&self, id: &str, _timeout_secs: Option<u64>) -> Result<()> {
+            if self.fail_stop_for == Some(id) {
+                bail!("stop failed");
+            }
+            self.record("stop", id);
+            Ok(())
+        }
+
+        fn start(&self, id: &str) -> Result<()> {
+            self.record("start", id);
+            Ok(())
+        }
+
+        fn pause(&self, id: &str) -> Result<()> {
+            self.record("pause", id);
+            Ok(())
+        }
+
+        fn unpause(&self, id: &str) -> Result<()> {
+            self.record("unpause", id);
+            Ok(())
+        }
+    }
+
+    fn config(mode: Option<QuiesceMode>) -> DockerConfig {
+        DockerConfig {
+            socket_path: None,
+            container_names: None,
+            compose_project: Some(String::from("app")),
+            quiesce_mode: mode,
+            stop_timeout: None,
+        }
+    }
+
+    #[test]
+    fn run_with_quiesced_containers_stops_runs_inner_starts_in_reverse() {
+        let client = MockDockerClient::new(vec!["web", "worker"]);
+        let inner_called = RefCell::new(false);
+
+        let result = run_with_quiesced_containers(&client, &config(None), || {
+            *inner_called.borrow_mut() = true;
+            Ok(())
+        });
+
+        assert!(result.is_ok());
+        assert!(*inner_called.borrow(), "Expected inner to be called");
+        assert_eq!(
+            client.calls(),
+            vec!["stop web", "stop worker", "start worker", "start web"]
+        );
+    }
+
+    #[test]
+    fn run_with_quiesced_containers_given_pause_mode_pauses_and_unpauses() {
+        let client = MockDockerClient::new(vec!["web"]);
+
+        let result =
+            run_with_quiesced_containers(&client, &config(Some(QuiesceMode::Pause)), || Ok(()));
+
+        assert!(result.is_ok());
+        assert_eq!(client.calls(), vec!["pause web", "unpause web"]);
+    }
+
+    #[test]
+    fn run_with_quiesced_containers_inner_failure_still_restarts() {
+        let client = MockDockerClient::new(vec!["web"]);
+
+        let result =
+            run_with_quiesced_containers(&client, &config(None), || Err(anyhow!("backup failed")));
+
+        assert!(result.is_err(), "Expected the inner error to be propagated");
+        assert_eq!(client.calls(), vec!["stop web", "start web"]);
+    }
+
+    #[test]
+    fn run_with_quiesced_containers_stop_failure_restarts_stopped_and_skips_inner() {
+        let mut client = MockDockerClient::new(vec!["web", "worker"]);
+        client.fail_stop_for = Some("worker");
+        let inner_called = RefCell::new(false);
+
+        let result = run_with_quiesced_containers(&client, &config(None), || {
+            *inner_called.borrow_mut() = true;
+            Ok(())
+        });
+
+        assert!(result.is_err());
+        assert!(!*inner_called.borrow(), "Expected inner to be skipped");
+        assert_eq!(client.calls(), vec!["stop web", "start web"]);
+    }
+
+    #[test]
+    fn run_with_quiesced_containers_given_names_and_project_quiesces_either_once() {
+        let mut client = MockDockerClient::new(vec!["web", "worker"]);
+        client.named_containers = vec!["web", "db"];
+        let mut config = config(None);
+        config.container_names = Some(vec![String::from("web"), String::from("db")]);
+
+        let result = run_with_quiesced_containers(&client, &config, || Ok(()));
+
+        assert!(result.is_ok());
+        assert_eq!(
+            client.calls(),
+            vec![
+                "stop web",
+                "stop db",
+                "stop worker",
+                "start worker",
+                "start db",
+                "start web"
+            ]
+        );
+    }
+
+    #[test]
+    fn run_with_quiesced_containers_given_no_filters_returns_error() {
+        let client = MockDockerClient::new(vec![]);
+        let mut config = config(None);
+        config.compose_project = None;
+
+        let result = run_with_quiesced_containers(&client, &config, || Ok(()));
+
+        assert!(result.is_err());
+    }
+}
@@ -12,31 +12,55 @@ use std::fs::remove_dir_all;
 use std::path::{Path, PathBuf};
 use tracing::{debug, enabled, info, Level, warn};
 
-pub fn backup_files(app_config: &AppConfig, shutdown_rx: &Receiver<()>) -> Result<()> {
+/// The name of the backup made by this run within the destination path, e.g. `YYYY-mm-DD_HHMMSS_BackupName`.
+pub fn backup_name(app_config: &AppConfig) -> PathBuf {
+    let now: DateTime<Utc> = Utc::now();
+    PathBuf::from(format!(
+        "{}_{}",
+        now.format("%F_%H%M%S"),
+        &app_config.backup_name
+    ))
+}
+
+/// Backs up the source path, merged with the contents of `staging_path` if given, using the configured backup type,
+/// leaving out `excludes`, which are paths relative to the source path.
+pub fn backup_files(
+    app_config: &AppConfig,
+    filename: &Path,
+    staging_path: Option<&Path>,
+    excludes: &[PathBuf],
+    shutdown_rx: &Receiver<()>,
+) -> Result<()> {
     info!("Beginning file backup.");
 
-    let has_nonempty_files = has_nonempty_files(&app_config.source_path)?;
-    if !has_nonempty_files {
+    // Database backups may be staged elsewhere or already written into the new backup directory.
+    let new_backup_path = app_config.destination_path.join(filename);
+    let paths = [
+        Some(app_config.source_path.as_path()),
+        Some(new_backup_path.as_path()),
+        staging_path,
+    ];
+    let mut has_files = false;
+    for path in paths.into_iter().flatten() {
+        if has_nonempty_files(path)? {
+            has_files = true;
+            break;
+        }
+    }
+    if !has_files {
         warn!("Source path is empty, consists of only empty directories, and/or consists only of zero byte files. No backup will be made.");
         return Ok(());
     }
 
-    let now: DateTime<Utc> = Utc::now();
-    let filename = PathBuf::from(format!(
-        "{}_{}",
-        now.format("%F_%H%M%S"),
-        &app_config.backup_name
-    ));
-
-    let mut previous_backups = get_previous_backups(app_config)?;
+    let mut previous_backups = get_previous_backups(app_config, filename)?;
 
     let latest = previous_backups.peek().map(|e| e.path.as_path());
-    let client =
-        get_backup_client(app_config, latest).context("Failed to create backup client.")?;
+    let client = get_backup_client(app_config, staging_path, excludes, latest)
+        .context("Failed to create backup client.")?;
 
     info!(filename=%filename.display(), "Creating backup.");
     client
-        .run_backup(&filename, shutdown_rx)
+        .run_backup(filename, shutdown_rx)
         .context("Error while making backup.")?;
 
     let backup_count = previous_backups.len() + 1; // Includes the backup we just made.
@@ -64,16 +88,12 @@ pub fn backup_files(app_config: &AppConfig, shutdown_rx: &Receiver<()>) -> Resul
             .try_for_each(|b| {
                 remove_dir_all(&b.path)
                     .context("Error while deleting older backup.")
-                    .map(|r| {
+                    .inspect(|_| {
                         count += 1;
                         info!(path=%b.path.display(), "Deleted backup at the given path.");
-                        r
                     })
             })
-            .map(|r| {
-                info!(total_deletes = count, "Finished deleting oldest backups.");
-                r
-            })
+            .inspect(|_| info!(total_deletes = count, "Finished deleting oldest backups."))
     }
 }
 
@@ -96,7 +116,10 @@ fn has_nonempty_files(dir: &Path) -> Result<bool> {
     Ok(false)
 }
 
-fn get_previous_backups(app_config: &AppConfig) -> Result<BinaryHeap<DirEntryPriority>> {
+fn get_previous_backups(
+    app_config: &AppConfig,
+    filename: &Path,
+) -> Result<BinaryHeap<DirEntryPriority>> {
     let dir = &app_config.destination_path;
     if !dir.is_dir() {
         bail!("Went to find the oldest file in the destination directory but was give a path to a file instead.");
@@ -104,7 +127,12 @@ fn get_previous_backups(app_config: &AppConfig) -> Result<BinaryHeap<DirEntryPri
 
     let mut heap: BinaryHeap<DirEntryPriority> = BinaryHeap::new();
     for entry in fs::read_dir(dir)? {
-        heap.push(DirEntryPriority::new(entry?)?);
+        let entry = entry?;
+        // The new backup's directory already exists when database backups were written into it.
+        if entry.file_name() == filename.as_os_str() {
+            continue;
+        }
+        heap.push(DirEntryPriority::new(entry)?);
     }
 
     Ok(heap)
@@ -112,6 +140,8 @@ fn get_previous_backups(app_config: &AppConfig) -> Result<BinaryHeap<DirEntryPri
 
 fn get_backup_client<'a>(
     app_config: &'a AppConfig,
+    staging_path: Option<&'a Path>,
+    excludes: &'a [PathBuf],
     previous_backup: Option<&Path>,
 ) -> Result<Box<dyn BackupClient + 'a>> {
     let result: Box<dyn BackupClient + 'a> = match app_config
@@ -119,9 +149,18 @@ fn get_backup_client<'a>(
         .as_ref()
         .unwrap_or(&BackupType::Incremental)
     {
-        BackupType::Compressed => Box::new(tar::TarBackupClient::new(app_config)?),
+        BackupType::Compressed => Box::new(tar::TarBackupClient::new(
+            app_config,
+            staging_path,
+            excludes,
+        )?),
         BackupType::Incremental => {
-            Box::new(rsync::RsyncBackupClient::new(app_config, previous_backup)?)
+            Box::new(rsync::RsyncBackupClient::new(
+                app_config,
+                staging_path,
+                excludes,
+                previous_backup,
+            )?)
         }
     };
 
@@ -4,4 +4,4 @@ mod dir_entry_priority;
 mod rsync;
 mod tar;
 
-pub use backup::backup_files;
+pub use backup::{backup_files, backup_name};
@@ -5,6 +5,7 @@ use std::path::PathBuf;
 pub struct RsyncConfig {
     pub timeout: Option<u64>,
     pub exclude_file_path: Option<PathBuf>,
+    pub excludes: Option<Vec<String>>,
     pub destination_owner: Option<String>,
     pub destination_group: Option<String>,
     pub whole_file: Option<bool>,
@@ -15,12 +15,16 @@ pub const DEFAULT_TIMEOUT_SECS: u64 = 60 * 5; // 5 minutes
 pub struct RsyncBackupClient<'a> {
     app_config: &'a AppConfig,
     rsync_config: RsyncConfig,
+    staging_path: Option<&'a Path>,
+    excluded_paths: &'a [PathBuf],
     previous_backup: Option<PathBuf>,
 }
 
 impl<'a> RsyncBackupClient<'a> {
     pub fn new(
         app_config: &'a AppConfig,
+        staging_path: Option<&'a Path>,
+        excluded_paths: &'a [PathBuf],
         previous_backup: Option<&Path>,
     ) -> Result<RsyncBackupClient<'a>> {
         let rsync_config = envy::prefixed(INCREMENTAL_CONFIG_PREFIX)
@@ -30,6 +34,8 @@ impl<'a> RsyncBackupClient<'a> {
         Ok(RsyncBackupClient {
             app_config,
             rsync_config,
+            staging_path,
+            excluded_paths,
             previous_backup: previous_backup.map(PathBuf::from),
         })
     }
@@ -66,16 +72,23 @@ impl<'a> RsyncBackupClient<'a> {
                 .arg("--super");
         }
 
-        if let Some(whole_file) = &self.rsync_config.whole_file {
-            if *whole_file {
-                builder_ref = builder_ref.arg("--whole-file");
-            }
+        if self.rsync_config.whole_file.unwrap_or(false) {
+            builder_ref = builder_ref.arg("--whole-file");
         }
 
         if let Some(excludes) = &self.rsync_config.exclude_file_path {
             builder_ref = builder_ref.arg("--exclude-from").arg(excludes.as_os_str());
         }
 
+        for exclude in self.rsync_config.excludes.iter().flatten() {
+            builder_ref = builder_ref.arg(format!("--exclude={}", exclude));
+        }
+
+        // A leading slash anchors the pattern to the root of the transfer.
+        for path in self.excluded_paths {
+            builder_ref = builder_ref.arg(format!("--exclude=/{}", path.display()));
+        }
+
         if let Some(previous) = &self.previous_backup {
             builder_ref = builder_ref.arg("--link-dest").arg(previous.as_os_str());
         }
@@ -85,8 +98,14 @@ impl<'a> RsyncBackupClient<'a> {
         let mut final_source = PathBuf::from(&self.app_config.source_path);
         final_source.push("");
 
+        builder_ref = builder_ref.arg(final_source.as_os_str());
+
+        // The contents of the staging path are merged with those of the source path.
+        if let Some(staging_path) = self.staging_path {
+            builder_ref = builder_ref.arg(staging_path.join("").as_os_str());
+        }
+
         builder_ref
-            .arg(final_source.as_os_str())
             .arg(destination_filepath.as_os_str())
             .spawn()
             .context("Error while starting tar process and returning Popen.")
@@ -5,4 +5,5 @@ use std::path::PathBuf;
 pub struct TarConfig {
     pub timeout: Option<u64>,
     pub exclude_file_path: Option<PathBuf>,
+    pub excludes: Option<Vec<String>>,
 }
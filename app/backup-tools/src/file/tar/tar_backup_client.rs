@@ -5,7 +5,7 @@ use crate::file::tar::config::TarConfig;
 use anyhow::Context;
 use anyhow::Result;
 use crossbeam::channel::Receiver;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Child;
 use std::time::Duration;
 use tracing::trace_span;
@@ -16,10 +16,16 @@ pub const DEFAULT_TIMEOUT_SECS: u64 = 60 * 60; // 60 minutes
 pub struct TarBackupClient<'a> {
     app_config: &'a AppConfig,
     tar_config: TarConfig,
+    staging_path: Option<&'a Path>,
+    excluded_paths: &'a [PathBuf],
 }
 
 impl<'a> TarBackupClient<'a> {
-    pub fn new(app_config: &'a AppConfig) -> Result<TarBackupClient<'a>> {
+    pub fn new(
+        app_config: &'a AppConfig,
+        staging_path: Option<&'a Path>,
+        excluded_paths: &'a [PathBuf],
+    ) -> Result<TarBackupClient<'a>> {
         let tar_config = envy::prefixed(COMPRESSED_CONFIG_PREFIX)
             .from_env::<TarConfig>()
             .context("Error while loading tar config.")?;
@@ -27,6 +33,8 @@ impl<'a> TarBackupClient<'a> {
         Ok(TarBackupClient {
             app_config,
             tar_config,
+            staging_path,
+            excluded_paths,
         })
     }
     fn execute_tar(&self, destination_filepath: &Path) -> Result<Child> {
@@ -41,10 +49,26 @@ impl<'a> TarBackupClient<'a> {
             builder_ref = builder_ref.arg("--exclude-from").arg(excludes.as_os_str());
         }
 
-        builder_ref
+        for exclude in self.tar_config.excludes.iter().flatten() {
+            builder_ref = builder_ref.arg(format!("--exclude={}", exclude));
+        }
+
+        // Archive members are named relative to the source path, which is archived as `.`.
+        for path in self.excluded_paths {
+            builder_ref = builder_ref.arg(format!("--exclude=./{}", path.display()));
+        }
+
+        builder_ref = builder_ref
             .arg("-C")
             .arg(self.app_config.source_path.as_os_str())
-            .arg(".")
+            .arg(".");
+
+        // The contents of the staging path are archived alongside those of the source path.
+        if let Some(staging_path) = self.staging_path {
+            builder_ref = builder_ref.arg("-C").arg(staging_path.as_os_str()).arg(".");
+        }
+
+        builder_ref
             .spawn()
             .context("Error while starting tar process and returning Popen.")
     }
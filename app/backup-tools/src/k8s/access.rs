@@ -0,0 +1,249 @@
+use crate::k8s::config::K8S_PREFIX;
+use crate::k8s::model::access_review::ResourceAttributes;
+use crate::k8s::{DefaultK8sClient, K8sClient, K8sConfig};
+use anyhow::{bail, Context, Result};
+use envy::prefixed;
+use tracing::{error, info, trace_span};
+
+/// Verifies that the service account may perform every Kubernetes API operation the enabled features rely on, so
+/// that a missing `Role` rule is found before a workload is scaled down rather than when scaling it back up.
+pub fn check_permissions(scaling_enabled: bool, exec_hooks_enabled: bool) -> Result<()> {
+    let span = trace_span!("k8s_access");
+    let _entered = span.enter();
+
+    let k8s_config = prefixed(K8S_PREFIX).from_env::<K8sConfig>()?;
+    let k8s_client = DefaultK8sClient::new(&k8s_config)?;
+    let service_namespace = k8s_config.resolve_namespace()?;
+
+    let permissions = required_permissions(
+        &k8s_config,
+        &service_namespace,
+        scaling_enabled,
+        exec_hooks_enabled,
+    );
+
+    verify_permissions(&k8s_client, &permissions)
+}
+
+/// The permission to read the configured workload's annotations, checked before reading them.
+pub fn annotation_permissions(config: &K8sConfig, namespace: &str) -> Vec<ResourceAttributes> {
+    let workload_type = &config.workload_type;
+    vec![attributes(
+        namespace,
+        "get",
+        workload_type.api_group(),
+        workload_type.resource_name(),
+        None,
+        Some(&config.service_deployment_name),
+    )]
+}
+
+/// The permissions to read the secrets referenced by the workload's annotations.
+pub fn secret_permissions(namespace: &str, secret_names: &[&str]) -> Vec<ResourceAttributes> {
+    secret_names
+        .iter()
+        .map(|name| attributes(namespace, "get", "", "secrets", None, Some(name)))
+        .collect()
+}
+
+fn required_permissions(
+    config: &K8sConfig,
+    namespace: &str,
+    scaling_enabled: bool,
+    exec_hooks_enabled: bool,
+) -> Vec<ResourceAttributes> {
+    let attributes =
+        |verb: &str, group: &str, resource: &str, subresource: Option<&str>, name: Option<&str>| {
+            attributes(namespace, verb, group, resource, subresource, name)
+        };
+    let mut permissions = Vec::new();
+
+    if scaling_enabled {
+        let workload_type = &config.workload_type;
+        let name = Some(config.service_deployment_name.as_str());
+        for verb in ["get", "patch"] {
+            permissions.push(attributes(
+                verb,
+                workload_type.api_group(),
+                workload_type.resource_name(),
+                None,
+                name,
+            ));
+        }
+    }
+
+    if exec_hooks_enabled {
+        permissions.push(attributes("list", "", "pods", None, None));
+        for verb in ["get", "create"] {
+            permissions.push(attributes(verb, "", "pods", Some("exec"), None));
+        }
+    }
+
+    permissions
+}
+
+fn attributes(
+    namespace: &str,
+    verb: &str,
+    group: &str,
+    resource: &str,
+    subresource: Option<&str>,
+    name: Option<&str>,
+) -> ResourceAttributes {
+    ResourceAttributes {
+        namespace: String::from(namespace),
+        verb: String::from(verb),
+        group: String::from(group),
+        resource: String::from(resource),
+        subresource: subresource.map(String::from),
+        name: name.map(String::from),
+    }
+}
+
+pub fn verify_permissions(
+    client: &impl K8sClient,
+    permissions: &[ResourceAttributes],
+) -> Result<()> {
+    let mut denied = Vec::new();
+
+    for permission in permissions {
+        let allowed = client
+            .is_allowed(permission)
+            .context("Failed to review access to the Kubernetes API.")?;
+        let description = describe(permission);
+
+        if allowed {
+            info!(permission=%description, "Access granted.");
+        } else {
+            error!(permission=%description, "Access denied.");
+            denied.push(description);
+        }
+    }
+
+    if !denied.is_empty() {
+        bail!(
+            "The service account is missing permissions required for the backup: {}",
+            denied.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+fn describe(permission: &ResourceAttributes) -> String {
+    let mut resource = if permission.group.is_empty() {
+        permission.resource.clone()
+    } else {
+        format!("{}.{}", &permission.resource, &permission.group)
+    };
+
+    if let Some(subresource) = &permission.subresource {
+        resource = format!("{}/{}", resource, subresource);
+    }
+
+    if let Some(name) = &permission.name {
+        resource = format!("{} {}", resource, name);
+    }
+
+    format!("{} {}", &permission.verb, resource)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        annotation_permissions, describe, required_permissions, secret_permissions,
+        verify_permissions,
+    };
+    use crate::k8s::test_util::MockK8sClient;
+    use crate::k8s::workload_type::WorkloadType;
+    use crate::k8s::K8sConfig;
+    use std::path::PathBuf;
+
+    fn config(workload_type: WorkloadType) -> K8sConfig {
+        K8sConfig {
+            token_path: PathBuf::new(),
+            cacrt_path: PathBuf::new(),
+            service_host: String::from("localhost"),
+            service_port_https: 443,
+            service_namespace: None,
+            service_deployment_name: String::from("app"),
+            namespace_file_path: None,
+            workload_type,
+            suspend_timeout: None,
+        }
+    }
+
+    #[test]
+    fn required_permissions_given_scaling_requires_get_and_patch_on_workload() {
+        let permissions = required_permissions(&config(WorkloadType::CronJob), "ns", true, false);
+
+        let descriptions: Vec<String> = permissions.iter().map(describe).collect();
+        assert_eq!(
+            descriptions,
+            vec!["get cronjobs.batch app", "patch cronjobs.batch app"]
+        );
+    }
+
+    #[test]
+    fn required_permissions_given_exec_hooks_requires_pod_access() {
+        let permissions =
+            required_permissions(&config(WorkloadType::Deployment), "ns", false, true);
+
+        let descriptions: Vec<String> = permissions.iter().map(describe).collect();
+        assert_eq!(
+            descriptions,
+            vec!["list pods", "get pods/exec", "create pods/exec"]
+        );
+    }
+
+    #[test]
+    fn required_permissions_given_nothing_enabled_is_empty() {
+        let permissions =
+            required_permissions(&config(WorkloadType::Deployment), "ns", false, false);
+        assert!(permissions.is_empty());
+    }
+
+    #[test]
+    fn annotation_permissions_require_get_on_workload_and_secrets() {
+        let permissions = annotation_permissions(&config(WorkloadType::DaemonSet), "ns");
+        let secrets = secret_permissions("ns", &["app-db", "app-cache"]);
+
+        let descriptions: Vec<String> = permissions.iter().chain(&secrets).map(describe).collect();
+        assert_eq!(
+            descriptions,
+            vec![
+                "get daemonsets.apps app",
+                "get secrets app-db",
+                "get secrets app-cache"
+            ]
+        );
+    }
+
+    #[test]
+    fn verify_permissions_given_all_allowed_succeeds() {
+        let client = MockK8sClient::denying(vec![]);
+        let permissions = required_permissions(&config(WorkloadType::Deployment), "ns", true, true);
+
+        assert!(verify_permissions(&client, &permissions).is_ok());
+        assert_eq!(client.review_count(), 5);
+    }
+
+    #[test]
+    fn verify_permissions_given_denied_reports_every_denial() {
+        let client = MockK8sClient::denying(vec!["patch", "create"]);
+        let permissions =
+            required_permissions(&config(WorkloadType::StatefulSet), "ns", true, true);
+
+        let result = verify_permissions(&client, &permissions);
+
+        assert!(result.is_err());
+        let message = result.unwrap_err().to_string();
+        assert!(message.contains("patch statefulsets.apps app"));
+        assert!(message.contains("create pods/exec"));
+        assert_eq!(
+            client.review_count(),
+            5,
+            "Expected every permission to be reviewed"
+        );
+    }
+}
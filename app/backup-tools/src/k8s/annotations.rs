@@ -0,0 +1,350 @@
+use crate::k8s::access;
+use crate::k8s::config::K8S_PREFIX;
+use crate::k8s::{DefaultK8sClient, K8sClient, K8sConfig};
+use anyhow::{Context, Result};
+use envy::prefixed;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use tracing::{debug, info, trace_span, warn};
+
+pub const ANNOTATION_CONFIG_PREFIX: &str = "ANNOTATION_CONFIG_";
+pub const ANNOTATION_PREFIX: &str = "backup-tools/";
+const SECRET_SUFFIX: &str = "-secret";
+/// Annotations that may be set on a workload and the variables they set. Settings that select paths, credentials or
+/// other resources of backup-tools itself are deliberately left out, as anyone able to annotate the workload could
+/// otherwise redirect the backup.
+const ANNOTATION_SETTINGS: [(&str, &str); 17] = [
+    ("backup-type", "BACKUP_TYPE"),
+    ("postgres-backup-enabled", "POSTGRES_BACKUP_ENABLED"),
+    ("mongo-backup-enabled", "MONGO_BACKUP_ENABLED"),
+    ("mysql-backup-enabled", "MYSQL_BACKUP_ENABLED"),
+    ("redis-backup-enabled", "REDIS_BACKUP_ENABLED"),
+    ("sqlite-backup-enabled", "SQLITE_BACKUP_ENABLED"),
+    ("db-backup-phase", "DB_BACKUP_PHASE"),
+    ("postgres-backup-phase", "POSTGRES_BACKUP_PHASE"),
+    ("mongo-backup-phase", "MONGO_BACKUP_PHASE"),
+    ("mysql-backup-phase", "MYSQL_BACKUP_PHASE"),
+    ("redis-backup-phase", "REDIS_BACKUP_PHASE"),
+    ("sqlite-backup-phase", "SQLITE_BACKUP_PHASE"),
+    ("file-backup-phase", "FILE_BACKUP_PHASE"),
+    ("postgres-database-names", "POSTGRES_DATABASE_NAMES"),
+    ("postgres-discover-databases", "POSTGRES_DISCOVER_DATABASES"),
+    ("mongo-database-names", "MONGO_DATABASE_NAMES"),
+    ("mysql-database-names", "MYSQL_DATABASE_NAMES"),
+];
+/// Dumpers whose connection settings may be read from a `Secret` and the keys that are read from it.
+const SECRET_SETTINGS: [(&str, &[&str]); 4] = [
+    (
+        "POSTGRES",
+        &[
+            "URL",
+            "HOST",
+            "PORT",
+            "USERNAME",
+            "PASSWORD",
+            "DATABASE_NAME",
+        ],
+    ),
+    (
+        "MONGO",
+        &[
+            "URL",
+            "HOST",
+            "PORT",
+            "USERNAME",
+            "DATABASE_NAME",
+            "AUTHENTICATION_DATABASE_NAME",
+            "TLS_CERTIFICATE_KEY_FILE_PASSWORD",
+        ],
+    ),
+    ("MYSQL", &["URL", "HOST", "PORT", "USERNAME", "PASSWORD"]),
+    ("REDIS", &["HOST", "PORT", "USERNAME", "PASSWORD"]),
+];
+
+#[derive(Debug, Deserialize)]
+struct AnnotationConfig {
+    pub enabled: Option<bool>,
+}
+
+/// Settings derived from the annotations on a workload, prior to being merged into the environment.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct AnnotationSettings {
+    env_vars: Vec<(String, String)>,
+    /// Pairs of environment variable prefixes and the names of secrets to read variables from.
+    secrets: Vec<(String, String)>,
+}
+
+/// Reads `backup-tools/` annotations from the configured Kubernetes workload and exports them as environment
+/// variables so that the rest of backup-tools may be configured next to the application being backed up. Variables
+/// that are already set in the environment always take precedence over annotations.
+///
+/// Must be called before any other threads are started as it modifies the process's environment.
+pub fn apply_annotation_config() -> Result<()> {
+    let annotation_config = prefixed(ANNOTATION_CONFIG_PREFIX)
+        .from_env::<AnnotationConfig>()
+        .context("Error while loading annotation config.")?;
+    if !annotation_config.enabled.unwrap_or(false) {
+        return Ok(());
+    }
+
+    let span = trace_span!("k8s_annotations");
+    let _entered = span.enter();
+
+    let k8s_config = prefixed(K8S_PREFIX).from_env::<K8sConfig>()?;
+    let k8s_client = DefaultK8sClient::new(&k8s_config)?;
+    let service_namespace = k8s_config.resolve_namespace()?;
+
+    let env_vars = get_annotation_env_vars(&k8s_client, &k8s_config, &service_namespace)?;
+
+    for (key, value) in env_vars {
+        if env::var_os(&key).is_some() {
+            debug!(key=%key, "Environment variable already set, ignoring annotation.");
+            continue;
+        }
+
+        info!(key=%key, "Setting environment variable from workload annotation.");
+        // SAFETY: Called during startup before any other threads exist.
+        unsafe { env::set_var(&key, value) };
+    }
+
+    Ok(())
+}
+
+fn get_annotation_env_vars(
+    client: &impl K8sClient,
+    config: &K8sConfig,
+    namespace: &str,
+) -> Result<Vec<(String, String)>> {
+    access::verify_permissions(client, &access::annotation_permissions(config, namespace))?;
+    let annotations = client
+        .get_annotations(namespace, &config.service_deployment_name)
+        .context("Failed to retrieve workload annotations.")?;
+    let settings = parse_annotations(&annotations);
+    let mut env_vars = settings.env_vars;
+
+    let secret_names: Vec<&str> = settings
+        .secrets
+        .iter()
+        .map(|(_, name)| name.as_str())
+        .collect();
+    access::verify_permissions(
+        client,
+        &access::secret_permissions(namespace, &secret_names),
+    )?;
+
+    for (prefix, secret_name) in settings.secrets {
+        let secret = client
+            .get_secret(namespace, &secret_name)
+            .with_context(|| format!("Failed to retrieve secret {}.", secret_name))?;
+        let mut secret_vars: Vec<(String, String)> = secret
+            .into_iter()
+            .filter_map(|(key, value)| {
+                let env_var = secret_key_to_env_var(&prefix, &key);
+                if is_allowed_secret_var(&prefix, &env_var) {
+                    Some((env_var, value))
+                } else {
+                    warn!(key=%key, secret=%secret_name, "Ignoring unsupported key of secret.");
+                    None
+                }
+            })
+            .collect();
+        secret_vars.sort();
+        env_vars.extend(secret_vars);
+    }
+
+    Ok(env_vars)
+}
+
+fn parse_annotations(annotations: &HashMap<String, String>) -> AnnotationSettings {
+    let mut settings = AnnotationSettings::default();
+    let mut keys: Vec<&String> = annotations.keys().collect();
+    keys.sort();
+
+    for key in keys {
+        let Some(name) = key.strip_prefix(ANNOTATION_PREFIX) else {
+            continue;
+        };
+        let value = annotations[key].trim();
+
+        match name {
+            "scale" => settings.env_vars.push((
+                String::from("SCALE_DEPLOYMENT_ENABLED"),
+                String::from(value),
+            )),
+            "excludes" => {
+                let excludes = split_excludes(value);
+                settings
+                    .env_vars
+                    .push((String::from("INCR_EXCLUDES"), excludes.clone()));
+                settings
+                    .env_vars
+                    .push((String::from("COMPRESSED_EXCLUDES"), excludes));
+            }
+            _ => match name.strip_suffix(SECRET_SUFFIX) {
+                Some(prefix) => {
+                    let prefix = to_env_var_name(prefix);
+                    if SECRET_SETTINGS.iter().any(|(p, _)| *p == prefix) {
+                        settings.secrets.push((prefix, String::from(value)));
+                    } else {
+                        warn!(annotation=%key, "Ignoring secret reference for an unsupported prefix.");
+                    }
+                }
+                None => match ANNOTATION_SETTINGS.iter().find(|(n, _)| *n == name) {
+                    Some((_, env_var)) => settings
+                        .env_vars
+                        .push((String::from(*env_var), String::from(value))),
+                    None => warn!(annotation=%key, "Ignoring unsupported annotation."),
+                },
+            },
+        }
+    }
+
+    settings
+}
+
+/// Exclude patterns may be given one per line or comma separated; they are passed on comma separated.
+fn split_excludes(value: &str) -> String {
+    value
+        .split(['\n', ','])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<&str>>()
+        .join(",")
+}
+
+fn is_allowed_secret_var(prefix: &str, env_var: &str) -> bool {
+    SECRET_SETTINGS
+        .iter()
+        .filter(|(p, _)| *p == prefix)
+        .flat_map(|(_, keys)| keys.iter())
+        .any(|key| env_var == format!("{}_{}", prefix, key))
+}
+
+fn to_env_var_name(name: &str) -> String {
+    name.replace(['-', '.'], "_").to_uppercase()
+}
+
+fn secret_key_to_env_var(prefix: &str, key: &str) -> String {
+    let key = to_env_var_name(key);
+    let prefix = format!("{}_", prefix);
+    if key.starts_with(&prefix) {
+        key
+    } else {
+        format!("{}{}", prefix, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        is_allowed_secret_var, parse_annotations, secret_key_to_env_var, split_excludes,
+        AnnotationSettings,
+    };
+    use std::collections::HashMap;
+
+    fn annotations(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn pair(key: &str, value: &str) -> (String, String) {
+        (String::from(key), String::from(value))
+    }
+
+    #[test]
+    fn parse_annotations_maps_names_to_env_vars() {
+        let result = parse_annotations(&annotations(&[
+            ("backup-tools/backup-type", "COMPRESSED"),
+            ("backup-tools/postgres-backup-enabled", "true"),
+        ]));
+
+        assert_eq!(
+            result.env_vars,
+            vec![
+                pair("BACKUP_TYPE", "COMPRESSED"),
+                pair("POSTGRES_BACKUP_ENABLED", "true"),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_annotations_maps_scale_alias() {
+        let result = parse_annotations(&annotations(&[("backup-tools/scale", "false")]));
+        assert_eq!(
+            result.env_vars,
+            vec![pair("SCALE_DEPLOYMENT_ENABLED", "false")]
+        );
+    }
+
+    #[test]
+    fn parse_annotations_maps_excludes_to_both_file_backups() {
+        let result = parse_annotations(&annotations(&[("backup-tools/excludes", "cache/\n*.tmp")]));
+        assert_eq!(
+            result.env_vars,
+            vec![
+                pair("INCR_EXCLUDES", "cache/,*.tmp"),
+                pair("COMPRESSED_EXCLUDES", "cache/,*.tmp"),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_annotations_collects_secret_references() {
+        let result = parse_annotations(&annotations(&[("backup-tools/postgres-secret", "app-db")]));
+        assert_eq!(
+            result,
+            AnnotationSettings {
+                env_vars: vec![],
+                secrets: vec![pair("POSTGRES", "app-db")],
+            }
+        );
+    }
+
+    #[test]
+    fn parse_annotations_ignores_unsupported_settings_and_secret_prefixes() {
+        let result = parse_annotations(&annotations(&[
+            ("backup-tools/destination-path", "/tmp"),
+            ("backup-tools/kubernetes-service-namespace", "other"),
+            ("backup-tools/child-env-passthrough", "*"),
+            ("backup-tools/kubernetes-secret", "service-account-token"),
+        ]));
+        assert_eq!(result, AnnotationSettings::default());
+    }
+
+    #[test]
+    fn is_allowed_secret_var_given_known_keys_returns_true() {
+        assert!(is_allowed_secret_var("POSTGRES", "POSTGRES_PASSWORD"));
+        assert!(is_allowed_secret_var("MONGO", "MONGO_URL"));
+        assert!(!is_allowed_secret_var("POSTGRES", "POSTGRES_BIN_DIR"));
+        assert!(!is_allowed_secret_var("REDIS", "REDIS_COMMAND"));
+    }
+
+    #[test]
+    fn parse_annotations_ignores_other_annotations() {
+        let result = parse_annotations(&annotations(&[
+            ("deployment.kubernetes.io/revision", "3"),
+            ("backup-tools.example.com/scale", "false"),
+        ]));
+        assert_eq!(result, AnnotationSettings::default());
+    }
+
+    #[test]
+    fn secret_key_to_env_var_adds_missing_prefix() {
+        assert_eq!(
+            secret_key_to_env_var("POSTGRES", "password"),
+            "POSTGRES_PASSWORD"
+        );
+        assert_eq!(
+            secret_key_to_env_var("POSTGRES", "POSTGRES_URL"),
+            "POSTGRES_URL"
+        );
+    }
+
+    #[test]
+    fn split_excludes_drops_blank_entries() {
+        assert_eq!(split_excludes(" a/ ,\n\nb/\n"), "a/,b/");
+    }
+}
@@ -1,20 +1,65 @@
-use crate::k8s::model::workload::Deployment;
-use crate::k8s::{cert, K8sConfig};
-use anyhow::{anyhow, Context, Result};
+use crate::k8s::exec::{read_exec_output, EXEC_SUBPROTOCOL};
+use crate::k8s::model::access_review::{
+    ResourceAttributes, SelfSubjectAccessReview, SelfSubjectAccessReviewResponse,
+};
+use crate::k8s::model::pod::PodList;
+use crate::k8s::model::secret::Secret;
+use crate::k8s::model::workload::{CronJob, DaemonSet, Deployment, WorkloadMetadata};
+use crate::common::tls;
+use crate::k8s::K8sConfig;
+use anyhow::{anyhow, bail, Context, Result};
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use crossbeam::channel::Receiver;
+use rustls::pki_types::{CertificateDer, ServerName};
+use rustls::{ClientConfig, ClientConnection, RootCertStore, StreamOwned};
+use serde::de::DeserializeOwned;
 use serde::Serialize;
+use std::collections::HashMap;
+use std::net::TcpStream;
 use std::sync::Arc;
+use std::time::Duration;
 use tracing::debug;
-use ureq::http::{Request, Response};
+use tungstenite::client::IntoClientRequest;
+use ureq::http::{HeaderValue, Request, Response};
 use ureq::middleware::MiddlewareNext;
 use ureq::tls::{RootCerts, TlsConfig};
 use ureq::{Body, SendBody};
 use url::Url;
 use crate::k8s::workload_type::WorkloadType;
 
+const EXEC_READ_TIMEOUT_SECS: u64 = 5;
+
+/// Node selector label added to a `DaemonSet` pod template to suspend it; no node is expected to carry this label so
+/// all of the `DaemonSet`'s pods are removed until the label is taken away again.
+pub const SUSPEND_NODE_SELECTOR_KEY: &str = "backup-tools/suspended";
+
 pub trait K8sClient {
     fn get_available_replicas(&self, namespace: &str, name: &str) -> Result<i32>;
 
     fn scale(&self, namespace: &str, name: &str, count: i32) -> Result<()>;
+
+    fn is_suspended(&self, namespace: &str, name: &str) -> Result<bool>;
+
+    fn suspend(&self, namespace: &str, name: &str, suspended: bool) -> Result<()>;
+
+    fn list_running_pods(&self, namespace: &str, label_selector: &str) -> Result<Vec<String>>;
+
+    fn exec(
+        &self,
+        namespace: &str,
+        pod: &str,
+        container: Option<&str>,
+        command: &[&str],
+        timeout: Duration,
+        shutdown_rx: &Receiver<()>,
+    ) -> Result<i32>;
+
+    fn is_allowed(&self, attributes: &ResourceAttributes) -> Result<bool>;
+
+    fn get_annotations(&self, namespace: &str, name: &str) -> Result<HashMap<String, String>>;
+
+    fn get_secret(&self, namespace: &str, name: &str) -> Result<HashMap<String, String>>;
 }
 
 fn logging_middleware(req: Request<SendBody>, next: MiddlewareNext) -> Result<Response<Body>, ureq::Error> {
@@ -57,7 +102,10 @@ pub struct DefaultK8sClient {
     kube_base_url: Url,
     token: String,
     agent: ureq::Agent,
-    workload_type: String,
+    workload_type: WorkloadType,
+    service_host: String,
+    service_port: u16,
+    exec_tls_config: Arc<ClientConfig>,
 }
 
 impl DefaultK8sClient {
@@ -66,7 +114,8 @@ impl DefaultK8sClient {
         let token = DefaultK8sClient::get_token(config)?;
         debug!("Token Byte Length: {}", token.len());
 
-        let certs = cert::load(config)?;
+        let certs = tls::load_certs(Some(&config.cacrt_path))?;
+        let exec_tls_config = DefaultK8sClient::get_exec_tls_config(&certs);
         let tls_config = TlsConfig::builder()
             .root_certs(RootCerts::Specific(Arc::new(certs)))
             .build();
@@ -78,19 +127,35 @@ impl DefaultK8sClient {
 
         let agent = ureq::Agent::from(agent_config);
 
-        let workload_type = match config.workload_type {
-            WorkloadType::Deployment => String::from("deployments"),
-            WorkloadType::StatefulSet => String::from("statefulsets"),
-        };
-
         Ok(DefaultK8sClient {
             kube_base_url,
             token,
             agent,
-            workload_type,
+            workload_type: config.workload_type,
+            service_host: config.service_host.clone(),
+            service_port: config.service_port_https,
+            exec_tls_config,
         })
     }
 
+    /// ureq does not support WebSockets, so exec sessions are made over a separate rustls connection that trusts the
+    /// same certificates as the ureq agent.
+    fn get_exec_tls_config(certs: &[ureq::tls::Certificate<'static>]) -> Arc<ClientConfig> {
+        let mut root_store = RootCertStore::empty();
+        let (added, ignored) = root_store.add_parsable_certificates(
+            certs
+                .iter()
+                .map(|c| CertificateDer::from(c.der().to_vec())),
+        );
+        debug!(added, ignored, "Loaded certificates for exec sessions.");
+
+        Arc::new(
+            ClientConfig::builder()
+                .with_root_certificates(root_store)
+                .with_no_client_auth(),
+        )
+    }
+
     fn get_url(config: &K8sConfig) -> Result<Url> {
         let host_with_scheme = format!("https://{}", &config.service_host);
         let mut result = Url::parse(&host_with_scheme)?;
@@ -109,15 +174,21 @@ impl DefaultK8sClient {
     fn get_token(config: &K8sConfig) -> Result<String> {
         std::fs::read_to_string(&config.token_path).context("Failed to retrieve Kube token.")
     }
-}
 
-impl K8sClient for DefaultK8sClient {
-    fn get_available_replicas(&self, namespace: &str, name: &str) -> Result<i32> {
+    fn get_workload_url(&self, namespace: &str, name: &str) -> Result<Url> {
         let path = format!(
-            "/apis/apps/v1/namespaces/{}/{}/{}",
-            namespace, &self.workload_type, name
+            "/apis/{}/namespaces/{}/{}/{}",
+            self.workload_type.api_group_version(),
+            namespace,
+            self.workload_type.resource_name(),
+            name
         );
-        let url = self.kube_base_url.join(&path)?;
+
+        Ok(self.kube_base_url.join(&path)?)
+    }
+
+    fn get_workload<T: DeserializeOwned>(&self, namespace: &str, name: &str) -> Result<T> {
+        let url = self.get_workload_url(namespace, name)?;
 
         let response = self
             .agent
@@ -126,21 +197,13 @@ impl K8sClient for DefaultK8sClient {
             .header("Authorization", &format!("Bearer {}", &self.token))
             .call()?
             .body_mut()
-            .read_json::<Deployment>()?;
+            .read_json::<T>()?;
 
-        response
-            .status
-            .ok_or_else(|| anyhow!("Failed to retrieve workload status."))
-            .map(|r| r.available_replicas.unwrap_or(0))
+        Ok(response)
     }
 
-    fn scale(&self, namespace: &str, name: &str, count: i32) -> Result<()> {
-        let path = format!(
-            "/apis/apps/v1/namespaces/{}/{}/{}",
-            namespace, &self.workload_type, name
-        );
-        let url = self.kube_base_url.join(&path)?;
-        let body = ScalePatch::new(count);
+    fn patch_workload(&self, namespace: &str, name: &str, body: impl Serialize) -> Result<()> {
+        let url = self.get_workload_url(namespace, name)?;
 
         self.agent
             .patch(url.as_str())
@@ -153,6 +216,207 @@ impl K8sClient for DefaultK8sClient {
     }
 }
 
+impl K8sClient for DefaultK8sClient {
+    fn get_available_replicas(&self, namespace: &str, name: &str) -> Result<i32> {
+        match self.workload_type {
+            WorkloadType::DaemonSet => self
+                .get_workload::<DaemonSet>(namespace, name)?
+                .status
+                .ok_or_else(|| anyhow!("Failed to retrieve workload status."))
+                .map(|r| r.number_available.unwrap_or(0)),
+            WorkloadType::CronJob => Ok(self
+                .get_workload::<CronJob>(namespace, name)?
+                .status
+                .and_then(|s| s.active)
+                .map_or(0, |active| active.len() as i32)),
+            _ => self
+                .get_workload::<Deployment>(namespace, name)?
+                .status
+                .ok_or_else(|| anyhow!("Failed to retrieve workload status."))
+                .map(|r| r.available_replicas.unwrap_or(0)),
+        }
+    }
+
+    fn scale(&self, namespace: &str, name: &str, count: i32) -> Result<()> {
+        if !self.workload_type.is_scalable() {
+            bail!("Workload type {:?} cannot be scaled.", self.workload_type);
+        }
+
+        self.patch_workload(namespace, name, ScalePatch::new(count))
+    }
+
+    fn is_suspended(&self, namespace: &str, name: &str) -> Result<bool> {
+        match self.workload_type {
+            WorkloadType::CronJob => Ok(self
+                .get_workload::<CronJob>(namespace, name)?
+                .spec
+                .and_then(|s| s.suspend)
+                .unwrap_or(false)),
+            WorkloadType::DaemonSet => Ok(self
+                .get_workload::<DaemonSet>(namespace, name)?
+                .node_selector()
+                .is_some_and(|s| s.contains_key(SUSPEND_NODE_SELECTOR_KEY))),
+            _ => bail!("Workload type {:?} cannot be suspended.", self.workload_type),
+        }
+    }
+
+    fn suspend(&self, namespace: &str, name: &str, suspended: bool) -> Result<()> {
+        match self.workload_type {
+            WorkloadType::CronJob => {
+                self.patch_workload(namespace, name, SuspendPatch::new(suspended))
+            }
+            WorkloadType::DaemonSet => {
+                self.patch_workload(namespace, name, NodeSelectorPatch::new(suspended))
+            }
+            _ => bail!("Workload type {:?} cannot be suspended.", self.workload_type),
+        }
+    }
+
+    fn list_running_pods(&self, namespace: &str, label_selector: &str) -> Result<Vec<String>> {
+        let path = format!("/api/v1/namespaces/{}/pods", namespace);
+        let mut url = self.kube_base_url.join(&path)?;
+        url.query_pairs_mut()
+            .append_pair("labelSelector", label_selector);
+
+        let response = self
+            .agent
+            .get(url.as_str())
+            .header("Accept", "application/json")
+            .header("Authorization", &format!("Bearer {}", &self.token))
+            .call()?
+            .body_mut()
+            .read_json::<PodList>()?;
+
+        Ok(response
+            .items
+            .into_iter()
+            .filter(|p| p.is_running())
+            .map(|p| p.metadata.name)
+            .collect())
+    }
+
+    fn exec(
+        &self,
+        namespace: &str,
+        pod: &str,
+        container: Option<&str>,
+        command: &[&str],
+        timeout: Duration,
+        shutdown_rx: &Receiver<()>,
+    ) -> Result<i32> {
+        let path = format!("/api/v1/namespaces/{}/pods/{}/exec", namespace, pod);
+        let mut url = self.kube_base_url.join(&path)?;
+        {
+            let mut query = url.query_pairs_mut();
+            for arg in command {
+                query.append_pair("command", arg);
+            }
+            if let Some(c) = container {
+                query.append_pair("container", c);
+            }
+            query
+                .append_pair("stdout", "true")
+                .append_pair("stderr", "true");
+        }
+        url.set_scheme("wss")
+            .map_err(|_| anyhow!("Failed to set WebSocket scheme on exec URL."))?;
+        debug!("K8s Client Begin: EXEC {}", url);
+
+        let mut request = url.as_str().into_client_request()?;
+        let headers = request.headers_mut();
+        headers.insert(
+            "Authorization",
+            HeaderValue::from_str(&format!("Bearer {}", &self.token))?,
+        );
+        headers.insert(
+            "Sec-WebSocket-Protocol",
+            HeaderValue::from_static(EXEC_SUBPROTOCOL),
+        );
+
+        let server_name = ServerName::try_from(self.service_host.clone())
+            .context("Invalid Kubernetes service host for TLS.")?;
+        let connection = ClientConnection::new(self.exec_tls_config.clone(), server_name)?;
+        let tcp_stream = TcpStream::connect((self.service_host.as_str(), self.service_port))
+            .context("Failed to connect to the Kubernetes API for exec.")?;
+        let tls_stream = StreamOwned::new(connection, tcp_stream);
+
+        let (mut socket, response) = tungstenite::client(request, tls_stream)
+            .map_err(|e| anyhow!("Failed to open exec session: {}", e))?;
+        debug!("K8s Client End: EXEC {} - {}", url, response.status());
+
+        // Reads time out periodically so that the session's timeout and the shutdown channel can be checked.
+        socket
+            .get_ref()
+            .sock
+            .set_read_timeout(Some(Duration::from_secs(EXEC_READ_TIMEOUT_SECS)))?;
+
+        read_exec_output(&mut socket, timeout, shutdown_rx)
+    }
+
+    fn is_allowed(&self, attributes: &ResourceAttributes) -> Result<bool> {
+        let url = self
+            .kube_base_url
+            .join("/apis/authorization.k8s.io/v1/selfsubjectaccessreviews")?;
+
+        let response = self
+            .agent
+            .post(url.as_str())
+            .header("Accept", "application/json")
+            .header("Authorization", &format!("Bearer {}", &self.token))
+            .send_json(SelfSubjectAccessReview::new(attributes))?
+            .body_mut()
+            .read_json::<SelfSubjectAccessReviewResponse>()?;
+
+        let status = response
+            .status
+            .ok_or_else(|| anyhow!("Failed to retrieve access review status."))?;
+        if !status.allowed {
+            debug!(
+                reason = status.reason.as_deref().unwrap_or(""),
+                "Access review denied."
+            );
+        }
+
+        Ok(status.allowed)
+    }
+
+    fn get_annotations(&self, namespace: &str, name: &str) -> Result<HashMap<String, String>> {
+        Ok(self
+            .get_workload::<WorkloadMetadata>(namespace, name)?
+            .metadata
+            .and_then(|m| m.annotations)
+            .unwrap_or_default())
+    }
+
+    fn get_secret(&self, namespace: &str, name: &str) -> Result<HashMap<String, String>> {
+        let path = format!("/api/v1/namespaces/{}/secrets/{}", namespace, name);
+        let url = self.kube_base_url.join(&path)?;
+
+        let response = self
+            .agent
+            .get(url.as_str())
+            .header("Accept", "application/json")
+            .header("Authorization", &format!("Bearer {}", &self.token))
+            .call()?
+            .body_mut()
+            .read_json::<Secret>()?;
+
+        response
+            .data
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(key, value)| {
+                let decoded = BASE64_STANDARD
+                    .decode(value)
+                    .with_context(|| format!("Failed to decode key {} of secret {}.", key, name))?;
+                let decoded = String::from_utf8(decoded)
+                    .with_context(|| format!("Key {} of secret {} is not valid UTF-8.", key, name))?;
+                Ok((key, decoded))
+            })
+            .collect()
+    }
+}
+
 #[derive(Debug, Serialize)]
 struct ScalePatchSpec {
     pub replicas: i32,
@@ -170,3 +434,87 @@ impl ScalePatch {
         }
     }
 }
+
+#[derive(Debug, Serialize)]
+struct SuspendPatchSpec {
+    pub suspend: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct SuspendPatch {
+    spec: SuspendPatchSpec,
+}
+
+impl SuspendPatch {
+    pub fn new(suspend: bool) -> SuspendPatch {
+        SuspendPatch {
+            spec: SuspendPatchSpec { suspend },
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NodeSelectorPodSpec {
+    // A null value removes the key when applied as a strategic merge patch.
+    pub node_selector: HashMap<&'static str, Option<&'static str>>,
+}
+
+#[derive(Debug, Serialize)]
+struct NodeSelectorTemplate {
+    pub spec: NodeSelectorPodSpec,
+}
+
+#[derive(Debug, Serialize)]
+struct NodeSelectorPatchSpec {
+    pub template: NodeSelectorTemplate,
+}
+
+#[derive(Debug, Serialize)]
+struct NodeSelectorPatch {
+    spec: NodeSelectorPatchSpec,
+}
+
+impl NodeSelectorPatch {
+    pub fn new(suspended: bool) -> NodeSelectorPatch {
+        let value = if suspended { Some("true") } else { None };
+        NodeSelectorPatch {
+            spec: NodeSelectorPatchSpec {
+                template: NodeSelectorTemplate {
+                    spec: NodeSelectorPodSpec {
+                        node_selector: HashMap::from([(SUSPEND_NODE_SELECTOR_KEY, value)]),
+                    },
+                },
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NodeSelectorPatch, SuspendPatch};
+
+    #[test]
+    fn suspend_patch_serializes_suspend_flag() {
+        let json = serde_json::to_string(&SuspendPatch::new(true)).unwrap();
+        assert_eq!(json, r#"{"spec":{"suspend":true}}"#);
+    }
+
+    #[test]
+    fn node_selector_patch_given_suspended_adds_label() {
+        let json = serde_json::to_string(&NodeSelectorPatch::new(true)).unwrap();
+        assert_eq!(
+            json,
+            r#"{"spec":{"template":{"spec":{"nodeSelector":{"backup-tools/suspended":"true"}}}}}"#
+        );
+    }
+
+    #[test]
+    fn node_selector_patch_given_resumed_removes_label() {
+        let json = serde_json::to_string(&NodeSelectorPatch::new(false)).unwrap();
+        assert_eq!(
+            json,
+            r#"{"spec":{"template":{"spec":{"nodeSelector":{"backup-tools/suspended":null}}}}}"#
+        );
+    }
+}
@@ -1,7 +1,13 @@
+use anyhow::{anyhow, Result};
 use serde::Deserialize;
+use std::fs::read_to_string;
 use std::path::PathBuf;
+use tracing::error;
 use crate::k8s::workload_type::WorkloadType;
 
+pub const K8S_PREFIX: &str = "KUBERNETES_";
+pub const EXEC_PREFIX: &str = "KUBERNETES_EXEC_";
+
 #[derive(Debug, Deserialize)]
 pub struct K8sConfig {
     pub token_path: PathBuf,
@@ -11,5 +17,41 @@ pub struct K8sConfig {
     pub service_namespace: Option<String>,
     pub service_deployment_name: String,
     pub namespace_file_path: Option<PathBuf>,
-    pub workload_type: WorkloadType
+    pub workload_type: WorkloadType,
+    /// How long to wait, in seconds, for the pods of a suspended workload to finish.
+    pub suspend_timeout: Option<u64>,
+}
+
+impl K8sConfig {
+    pub fn resolve_namespace(&self) -> Result<String> {
+        self.service_namespace
+            .clone()
+            .or_else(|| self.read_namespace_file())
+            .ok_or_else(|| anyhow!("Failed to determine namespace."))
+    }
+
+    fn read_namespace_file(&self) -> Option<String> {
+        if let Some(path) = &self.namespace_file_path {
+            read_to_string(path).map_or_else(
+                |e| {
+                    error!(ex=?e, "Failed to load namespace from namespace file.");
+                    None
+                },
+                Some,
+            )
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExecConfig {
+    pub pod_selector: String,
+    pub container: Option<String>,
+    pub pre_command: Option<String>,
+    pub post_command: Option<String>,
+    pub timeout: Option<u64>,
+    /// The timeout of the post-backup command, which also bounds how long it delays a shutdown.
+    pub post_timeout: Option<u64>,
 }
@@ -0,0 +1,200 @@
+use crate::k8s::model::status::Status;
+use anyhow::{anyhow, bail, Context, Result};
+use crossbeam::channel::Receiver;
+use std::io::{ErrorKind, Read, Write};
+use std::time::{Duration, Instant};
+use tracing::{debug, error, error_span, info, info_span, warn};
+use tungstenite::{Message, WebSocket};
+
+/// The WebSocket subprotocol for the `pods/exec` subresource; each binary message is prefixed with its channel.
+pub const EXEC_SUBPROTOCOL: &str = "v4.channel.k8s.io";
+
+const STDOUT_CHANNEL: u8 = 1;
+const STDERR_CHANNEL: u8 = 2;
+const ERROR_CHANNEL: u8 = 3;
+
+/// Buffers output from the stdout and stderr channels and logs it line by line, using the same spans as
+/// `common::process` so that remote commands are logged like local child processes.
+#[derive(Default)]
+struct ExecOutput {
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+    status: Option<Status>,
+}
+
+impl ExecOutput {
+    fn handle_message(&mut self, data: &[u8]) -> Result<()> {
+        let Some((channel, payload)) = data.split_first() else {
+            return Ok(());
+        };
+
+        match *channel {
+            STDOUT_CHANNEL => {
+                self.stdout.extend_from_slice(payload);
+                log_lines(&mut self.stdout, false, false);
+            }
+            STDERR_CHANNEL => {
+                self.stderr.extend_from_slice(payload);
+                log_lines(&mut self.stderr, true, false);
+            }
+            ERROR_CHANNEL => {
+                if !payload.is_empty() {
+                    let status = serde_json::from_slice::<Status>(payload)
+                        .context("Failed to parse status from the exec error channel.")?;
+                    self.status = Some(status);
+                }
+            }
+            other => debug!(
+                channel = other,
+                "Ignoring message on unexpected exec channel."
+            ),
+        }
+
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<i32> {
+        log_lines(&mut self.stdout, false, true);
+        log_lines(&mut self.stderr, true, true);
+
+        self.status
+            .ok_or_else(|| anyhow!("Exec session closed without reporting a status."))?
+            .exit_code()
+    }
+}
+
+fn log_lines(buffer: &mut Vec<u8>, is_stderr: bool, flush: bool) {
+    let span = if is_stderr {
+        error_span!("stderr")
+    } else {
+        info_span!("stdout")
+    };
+    let _enter = span.enter();
+
+    while let Some(pos) = buffer.iter().position(|b| *b == b'\n') {
+        let line: Vec<u8> = buffer.drain(..=pos).collect();
+        log_line(&line[..line.len() - 1], is_stderr);
+    }
+
+    if flush && !buffer.is_empty() {
+        let line = std::mem::take(buffer);
+        log_line(&line, is_stderr);
+    }
+}
+
+fn log_line(line: &[u8], is_stderr: bool) {
+    let line = String::from_utf8_lossy(line);
+    let line = line.trim_end_matches('\r');
+    if is_stderr {
+        error!("{}", line);
+    } else {
+        info!("{}", line);
+    }
+}
+
+/// Reads the exec session until the API server closes it and returns the exit code of the remote command.
+///
+/// The underlying stream is expected to have a read timeout so that the timeout and shutdown channel can be checked
+/// periodically while the remote command runs.
+pub fn read_exec_output<S: Read + Write>(
+    socket: &mut WebSocket<S>,
+    timeout: Duration,
+    shutdown_rx: &Receiver<()>,
+) -> Result<i32> {
+    let start = Instant::now();
+    let mut output = ExecOutput::default();
+
+    loop {
+        if shutdown_rx.try_recv().is_ok() {
+            warn!("Received notification to shutdown, closing exec session.");
+            socket.close(None).unwrap_or(());
+            bail!("Closed exec session due to shutdown.");
+        }
+
+        if start.elapsed() > timeout {
+            warn!(
+                time_elapsed_seconds = start.elapsed().as_secs(),
+                timeout_seconds = timeout.as_secs(),
+                "Reached timeout while waiting for remote command completion, closing the exec session."
+            );
+            socket.close(None).unwrap_or(());
+            bail!("Closed exec session due to timeout.");
+        }
+
+        match socket.read() {
+            Ok(Message::Binary(data)) => output.handle_message(&data)?,
+            Ok(Message::Close(_)) => break,
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(e))
+                if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut =>
+            {
+                debug!(
+                    time_elapsed_seconds = start.elapsed().as_secs(),
+                    "Remote command still running."
+                );
+            }
+            Err(tungstenite::Error::ConnectionClosed) | Err(tungstenite::Error::AlreadyClosed) => {
+                break;
+            }
+            Err(e) => return Err(anyhow!(e)).context("Error while reading from exec session."),
+        }
+    }
+
+    output.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExecOutput;
+
+    #[test]
+    fn handle_message_given_success_status_returns_zero() {
+        let mut output = ExecOutput::default();
+        output.handle_message(b"\x01hello\nwor").unwrap();
+        output.handle_message(b"\x01ld\n").unwrap();
+        output
+            .handle_message(b"\x03{\"status\":\"Success\"}")
+            .unwrap();
+
+        assert!(
+            output.stdout.is_empty(),
+            "Expected complete lines to be drained"
+        );
+        assert_eq!(output.finish().unwrap(), 0);
+    }
+
+    #[test]
+    fn handle_message_buffers_partial_lines_per_channel() {
+        let mut output = ExecOutput::default();
+        output.handle_message(b"\x01partial").unwrap();
+        output.handle_message(b"\x02other").unwrap();
+
+        assert_eq!(output.stdout, b"partial");
+        assert_eq!(output.stderr, b"other");
+    }
+
+    #[test]
+    fn handle_message_given_non_zero_exit_code_returns_code() {
+        let mut output = ExecOutput::default();
+        let status = br#"{"status":"Failure","reason":"NonZeroExitCode","details":{"causes":[{"reason":"ExitCode","message":"2"}]}}"#;
+        output
+            .handle_message(&[&[3u8][..], status].concat())
+            .unwrap();
+
+        assert_eq!(output.finish().unwrap(), 2);
+    }
+
+    #[test]
+    fn finish_given_no_status_returns_error() {
+        let mut output = ExecOutput::default();
+        output.handle_message(b"\x01done\n").unwrap();
+
+        assert!(output.finish().is_err());
+    }
+
+    #[test]
+    fn handle_message_given_invalid_status_returns_error() {
+        let mut output = ExecOutput::default();
+        assert!(output.handle_message(b"\x03not json").is_err());
+    }
+}
@@ -0,0 +1,243 @@
+use crate::k8s::config::{ExecConfig, EXEC_PREFIX, K8S_PREFIX};
+use crate::k8s::{DefaultK8sClient, K8sClient, K8sConfig};
+use anyhow::{anyhow, bail, Context, Result};
+use crossbeam::channel::{never, Receiver};
+use envy::prefixed;
+use std::time::Duration;
+use tracing::{error, info, trace_span};
+
+const DEFAULT_TIMEOUT_SECS: u64 = 60 * 5; // 5 minutes
+
+pub fn run_with_hooks(
+    inner: impl FnOnce() -> Result<()>,
+    shutdown_rx: &Receiver<()>,
+) -> Result<()> {
+    let span = trace_span!("k8s_exec");
+    let _entered = span.enter();
+
+    let k8s_config = prefixed(K8S_PREFIX).from_env::<K8sConfig>()?;
+    let exec_config = prefixed(EXEC_PREFIX)
+        .from_env::<ExecConfig>()
+        .context("Error while loading exec hook config.")?;
+    let k8s_client = DefaultK8sClient::new(&k8s_config)?;
+    let service_namespace = k8s_config.resolve_namespace()?;
+
+    run_with_exec_hooks(
+        &k8s_client,
+        &service_namespace,
+        &exec_config,
+        inner,
+        shutdown_rx,
+    )
+}
+
+fn run_with_exec_hooks(
+    client: &impl K8sClient,
+    namespace: &str,
+    config: &ExecConfig,
+    inner: impl FnOnce() -> Result<()>,
+    shutdown_rx: &Receiver<()>,
+) -> Result<()> {
+    if let Some(command) = &config.pre_command {
+        info!("Running pre-backup command in application pod...");
+        let timeout = config.timeout.unwrap_or(DEFAULT_TIMEOUT_SECS);
+        if let Err(e) = run_hook(client, namespace, config, command, timeout, shutdown_rx) {
+            error!(
+                "Pre-backup command failed! Attempting to run the post-backup command before exiting."
+            );
+            run_post_hook(client, namespace, config);
+            return Err(e.context("Failed to run pre-backup command."));
+        }
+        info!("Finished running pre-backup command.");
+    }
+
+    let inner_result = inner();
+    if inner_result.is_err() {
+        error!(
+            "Executing inner backup process failed! Attempting to run the post-backup command anyway."
+        );
+    }
+
+    run_post_hook(client, namespace, config);
+
+    inner_result
+}
+
+/// Runs the post-backup command even after a shutdown was requested, as it undoes the pre-backup command; only its
+/// timeout limits how long it may take.
+fn run_post_hook(client: &impl K8sClient, namespace: &str, config: &ExecConfig) {
+    if let Some(command) = &config.post_command {
+        info!("Running post-backup command in application pod...");
+        let timeout = config
+            .post_timeout
+            .or(config.timeout)
+            .unwrap_or(DEFAULT_TIMEOUT_SECS);
+        match run_hook(client, namespace, config, command, timeout, &never()) {
+            Ok(_) => info!("Finished running post-backup command."),
+            Err(e) => error!(ex=?e, "Failed to run post-backup command."),
+        }
+    }
+}
+
+fn run_hook(
+    client: &impl K8sClient,
+    namespace: &str,
+    config: &ExecConfig,
+    command: &str,
+    timeout_secs: u64,
+    shutdown_rx: &Receiver<()>,
+) -> Result<()> {
+    let pod = client
+        .list_running_pods(namespace, &config.pod_selector)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| {
+            anyhow!(
+                "No running pod found matching label selector: {}",
+                &config.pod_selector
+            )
+        })?;
+    info!(pod=%pod, "Executing command in pod.");
+    let exit_code = client.exec(
+        namespace,
+        &pod,
+        config.container.as_deref(),
+        &["/bin/sh", "-c", command],
+        Duration::from_secs(timeout_secs),
+        shutdown_rx,
+    )?;
+
+    if exit_code != 0 {
+        bail!(
+            "Command in pod {} exited with non-success exit code {}.",
+            pod,
+            exit_code
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::run_with_exec_hooks;
+    use crate::k8s::config::ExecConfig;
+    use crate::k8s::test_util::MockK8sClient;
+    use anyhow::anyhow;
+    use crossbeam::channel::unbounded;
+    use std::cell::RefCell;
+
+    fn config() -> ExecConfig {
+        ExecConfig {
+            pod_selector: String::from("app=nextcloud"),
+            container: None,
+            pre_command: Some(String::from("occ maintenance:mode --on")),
+            post_command: Some(String::from("occ maintenance:mode --off")),
+            timeout: None,
+            post_timeout: None,
+        }
+    }
+
+    #[test]
+    fn run_with_exec_hooks_runs_pre_inner_post_in_order() {
+        let client = MockK8sClient::with_pods(vec!["app-1"], vec![0, 0]);
+        let (_tx, rx) = unbounded();
+        let inner_called = RefCell::new(false);
+
+        let result = run_with_exec_hooks(
+            &client,
+            "ns",
+            &config(),
+            || {
+                assert_eq!(
+                    client.exec_commands().len(),
+                    1,
+                    "Expected pre command before inner"
+                );
+                *inner_called.borrow_mut() = true;
+                Ok(())
+            },
+            &rx,
+        );
+
+        assert!(result.is_ok());
+        assert!(*inner_called.borrow(), "Expected inner to be called");
+        assert_eq!(
+            client.exec_commands(),
+            vec!["occ maintenance:mode --on", "occ maintenance:mode --off"]
+        );
+    }
+
+    #[test]
+    fn run_with_exec_hooks_inner_failure_still_runs_post() {
+        let client = MockK8sClient::with_pods(vec!["app-1"], vec![0, 0]);
+        let (_tx, rx) = unbounded();
+
+        let result = run_with_exec_hooks(
+            &client,
+            "ns",
+            &config(),
+            || Err(anyhow!("backup failed")),
+            &rx,
+        );
+
+        assert!(result.is_err(), "Expected the inner error to be propagated");
+        assert_eq!(client.exec_commands().len(), 2);
+    }
+
+    #[test]
+    fn run_with_exec_hooks_given_shutdown_during_backup_still_runs_post() {
+        let client = MockK8sClient::with_pods(vec!["app-1"], vec![0, 0]);
+        let (tx, rx) = unbounded();
+
+        let result = run_with_exec_hooks(
+            &client,
+            "ns",
+            &config(),
+            || {
+                tx.send(()).unwrap();
+                Err(anyhow!("Killed process due to shutdown."))
+            },
+            &rx,
+        );
+
+        assert!(result.is_err());
+        assert_eq!(
+            client.exec_commands(),
+            vec!["occ maintenance:mode --on", "occ maintenance:mode --off"]
+        );
+    }
+
+    #[test]
+    fn run_with_exec_hooks_pre_failure_skips_inner_and_runs_post() {
+        let client = MockK8sClient::with_pods(vec!["app-1"], vec![1, 0]);
+        let (_tx, rx) = unbounded();
+        let inner_called = RefCell::new(false);
+
+        let result = run_with_exec_hooks(
+            &client,
+            "ns",
+            &config(),
+            || {
+                *inner_called.borrow_mut() = true;
+                Ok(())
+            },
+            &rx,
+        );
+
+        assert!(result.is_err());
+        assert!(!*inner_called.borrow(), "Expected inner to be skipped");
+        assert_eq!(client.exec_commands().len(), 2);
+    }
+
+    #[test]
+    fn run_with_exec_hooks_given_no_running_pods_returns_error() {
+        let client = MockK8sClient::with_pods(vec![], vec![]);
+        let (_tx, rx) = unbounded();
+
+        let result = run_with_exec_hooks(&client, "ns", &config(), || Ok(()), &rx);
+
+        assert!(result.is_err());
+        assert!(client.exec_commands().is_empty());
+    }
+}
@@ -1,9 +1,14 @@
 mod client;
 
-mod cert;
+pub mod access;
+pub mod annotations;
 mod config;
+mod exec;
+pub mod hooks;
 mod model;
 pub mod scale;
+#[cfg(test)]
+mod test_util;
 mod workload_type;
 
 use client::{DefaultK8sClient, K8sClient};
@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceAttributes {
+    pub namespace: String,
+    pub verb: String,
+    pub group: String,
+    pub resource: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subresource: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfSubjectAccessReview<'a> {
+    api_version: &'static str,
+    kind: &'static str,
+    spec: SelfSubjectAccessReviewSpec<'a>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SelfSubjectAccessReviewSpec<'a> {
+    resource_attributes: &'a ResourceAttributes,
+}
+
+impl<'a> SelfSubjectAccessReview<'a> {
+    pub fn new(resource_attributes: &'a ResourceAttributes) -> SelfSubjectAccessReview<'a> {
+        SelfSubjectAccessReview {
+            api_version: "authorization.k8s.io/v1",
+            kind: "SelfSubjectAccessReview",
+            spec: SelfSubjectAccessReviewSpec {
+                resource_attributes,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfSubjectAccessReviewResponse {
+    pub status: Option<SubjectAccessReviewStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubjectAccessReviewStatus {
+    pub allowed: bool,
+    pub reason: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ResourceAttributes, SelfSubjectAccessReview, SelfSubjectAccessReviewResponse};
+
+    #[test]
+    fn serialize_request_omits_missing_subresource_and_name() {
+        let attributes = ResourceAttributes {
+            namespace: String::from("ns"),
+            verb: String::from("list"),
+            group: String::new(),
+            resource: String::from("pods"),
+            subresource: None,
+            name: None,
+        };
+        let json = serde_json::to_string(&SelfSubjectAccessReview::new(&attributes)).unwrap();
+        assert_eq!(
+            json,
+            r#"{"apiVersion":"authorization.k8s.io/v1","kind":"SelfSubjectAccessReview","spec":{"resourceAttributes":{"namespace":"ns","verb":"list","group":"","resource":"pods"}}}"#
+        );
+    }
+
+    #[test]
+    fn deserialize_allowed_response() {
+        let json = r#"{"status":{"allowed":true}}"#;
+        let response: SelfSubjectAccessReviewResponse = serde_json::from_str(json).unwrap();
+        assert!(response.status.unwrap().allowed);
+    }
+
+    #[test]
+    fn deserialize_denied_response_with_reason() {
+        let json = r#"{"status":{"allowed":false,"reason":"no RBAC policy matched"}}"#;
+        let response: SelfSubjectAccessReviewResponse = serde_json::from_str(json).unwrap();
+        let status = response.status.unwrap();
+        assert!(!status.allowed);
+        assert_eq!(status.reason.as_deref(), Some("no RBAC policy matched"));
+    }
+}
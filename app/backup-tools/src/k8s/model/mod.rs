@@ -1 +1,5 @@
+pub mod access_review;
+pub mod pod;
+pub mod secret;
+pub mod status;
 pub mod workload;
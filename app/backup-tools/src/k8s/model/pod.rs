@@ -0,0 +1,65 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PodList {
+    #[serde(default)]
+    pub items: Vec<Pod>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Pod {
+    pub metadata: ObjectMeta,
+    pub status: Option<PodStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectMeta {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PodStatus {
+    pub phase: Option<String>,
+}
+
+impl Pod {
+    pub fn is_running(&self) -> bool {
+        self.status
+            .as_ref()
+            .and_then(|s| s.phase.as_deref())
+            .is_some_and(|p| p == "Running")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PodList;
+
+    #[test]
+    fn deserialize_with_running_and_pending_pods() {
+        let json = r#"{"items":[{"metadata":{"name":"app-1"},"status":{"phase":"Running"}},{"metadata":{"name":"app-2"},"status":{"phase":"Pending"}}]}"#;
+        let pods: PodList = serde_json::from_str(json).unwrap();
+        assert_eq!(pods.items.len(), 2);
+        assert_eq!(pods.items[0].metadata.name, "app-1");
+        assert!(pods.items[0].is_running());
+        assert!(!pods.items[1].is_running());
+    }
+
+    #[test]
+    fn deserialize_with_missing_status_is_not_running() {
+        let json = r#"{"items":[{"metadata":{"name":"app-1"}}]}"#;
+        let pods: PodList = serde_json::from_str(json).unwrap();
+        assert!(!pods.items[0].is_running());
+    }
+
+    #[test]
+    fn deserialize_with_missing_items() {
+        let json = r#"{"kind":"PodList"}"#;
+        let pods: PodList = serde_json::from_str(json).unwrap();
+        assert!(pods.items.is_empty());
+    }
+}
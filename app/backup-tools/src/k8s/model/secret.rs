@@ -0,0 +1,31 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Secret {
+    /// Values are base64 encoded by the Kubernetes API.
+    pub data: Option<HashMap<String, String>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Secret;
+
+    #[test]
+    fn deserialize_with_data() {
+        let json = r#"{"kind":"Secret","data":{"password":"cGFzcw=="}}"#;
+        let secret: Secret = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            secret.data.unwrap().get("password"),
+            Some(&String::from("cGFzcw=="))
+        );
+    }
+
+    #[test]
+    fn deserialize_with_missing_data() {
+        let json = r#"{"kind":"Secret"}"#;
+        let secret: Secret = serde_json::from_str(json).unwrap();
+        assert!(secret.data.is_none());
+    }
+}
@@ -0,0 +1,90 @@
+use anyhow::{anyhow, bail, Result};
+use serde::Deserialize;
+
+/// The `Status` object the Kubernetes API writes to the error channel once an exec session completes.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Status {
+    pub status: Option<String>,
+    pub message: Option<String>,
+    pub reason: Option<String>,
+    pub details: Option<StatusDetails>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusDetails {
+    #[serde(default)]
+    pub causes: Vec<StatusCause>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusCause {
+    pub reason: Option<String>,
+    pub message: Option<String>,
+}
+
+impl Status {
+    /// Returns the exit code of the remote command, or an error if the command could not be executed at all.
+    pub fn exit_code(&self) -> Result<i32> {
+        if self.status.as_deref() == Some("Success") {
+            return Ok(0);
+        }
+
+        if self.reason.as_deref() == Some("NonZeroExitCode") {
+            return self
+                .details
+                .iter()
+                .flat_map(|d| d.causes.iter())
+                .find(|c| c.reason.as_deref() == Some("ExitCode"))
+                .and_then(|c| c.message.as_deref())
+                .ok_or_else(|| anyhow!("Remote command failed without reporting an exit code."))?
+                .parse::<i32>()
+                .map_err(|e| anyhow!(e));
+        }
+
+        bail!(
+            "Remote command failed: {}",
+            self.message.as_deref().unwrap_or("no message provided")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Status;
+
+    #[test]
+    fn exit_code_given_success_returns_zero() {
+        let json = r#"{"metadata":{},"status":"Success"}"#;
+        let status: Status = serde_json::from_str(json).unwrap();
+        assert_eq!(status.exit_code().unwrap(), 0);
+    }
+
+    #[test]
+    fn exit_code_given_non_zero_exit_code_returns_code() {
+        let json = r#"{"metadata":{},"status":"Failure","message":"command terminated with non-zero exit code","reason":"NonZeroExitCode","details":{"causes":[{"reason":"ExitCode","message":"3"}]}}"#;
+        let status: Status = serde_json::from_str(json).unwrap();
+        assert_eq!(status.exit_code().unwrap(), 3);
+    }
+
+    #[test]
+    fn exit_code_given_other_failure_returns_error() {
+        let json = r#"{"metadata":{},"status":"Failure","message":"executable file not found in $PATH","reason":"InternalError"}"#;
+        let status: Status = serde_json::from_str(json).unwrap();
+        let result = status.exit_code();
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("executable file not found"));
+    }
+
+    #[test]
+    fn exit_code_given_non_zero_without_causes_returns_error() {
+        let json = r#"{"status":"Failure","reason":"NonZeroExitCode"}"#;
+        let status: Status = serde_json::from_str(json).unwrap();
+        assert!(status.exit_code().is_err());
+    }
+}
@@ -0,0 +1,57 @@
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CronJob {
+    pub spec: Option<CronJobSpec>,
+    pub status: Option<CronJobStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CronJobSpec {
+    pub suspend: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CronJobStatus {
+    // Only the number of active jobs is of interest, not the references themselves.
+    pub active: Option<Vec<IgnoredAny>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CronJob;
+
+    #[test]
+    fn deserialize_with_suspend_and_active_jobs() {
+        let json = r#"{"spec":{"suspend":true},"status":{"active":[{"name":"job-1"},{"name":"job-2"}]}}"#;
+        let cron_job: CronJob = serde_json::from_str(json).unwrap();
+        assert_eq!(cron_job.spec.unwrap().suspend, Some(true));
+        assert_eq!(cron_job.status.unwrap().active.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn deserialize_with_missing_suspend_field() {
+        let json = r#"{"spec":{"schedule":"* * * * *"}}"#;
+        let cron_job: CronJob = serde_json::from_str(json).unwrap();
+        assert_eq!(cron_job.spec.unwrap().suspend, None);
+    }
+
+    #[test]
+    fn deserialize_with_missing_active_field() {
+        let json = r#"{"status":{"lastScheduleTime":"2024-01-01T00:00:00Z"}}"#;
+        let cron_job: CronJob = serde_json::from_str(json).unwrap();
+        assert!(cron_job.status.unwrap().active.is_none());
+    }
+
+    #[test]
+    fn deserialize_with_missing_spec_and_status() {
+        let json = r#"{}"#;
+        let cron_job: CronJob = serde_json::from_str(json).unwrap();
+        assert!(cron_job.spec.is_none());
+        assert!(cron_job.status.is_none());
+    }
+}
@@ -0,0 +1,81 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DaemonSet {
+    pub spec: Option<DaemonSetSpec>,
+    pub status: Option<DaemonSetStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DaemonSetSpec {
+    pub template: Option<PodTemplateSpec>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PodTemplateSpec {
+    pub spec: Option<PodSpec>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PodSpec {
+    pub node_selector: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DaemonSetStatus {
+    pub number_available: Option<i32>,
+}
+
+impl DaemonSet {
+    pub fn node_selector(&self) -> Option<&HashMap<String, String>> {
+        self.spec
+            .as_ref()
+            .and_then(|s| s.template.as_ref())
+            .and_then(|t| t.spec.as_ref())
+            .and_then(|s| s.node_selector.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DaemonSet;
+
+    #[test]
+    fn deserialize_with_node_selector_and_status() {
+        let json = r#"{"spec":{"template":{"spec":{"nodeSelector":{"kubernetes.io/os":"linux"}}}},"status":{"numberAvailable":3}}"#;
+        let daemon_set: DaemonSet = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            daemon_set.node_selector().unwrap().get("kubernetes.io/os"),
+            Some(&String::from("linux"))
+        );
+        assert_eq!(daemon_set.status.unwrap().number_available, Some(3));
+    }
+
+    #[test]
+    fn deserialize_with_missing_node_selector() {
+        let json = r#"{"spec":{"template":{"spec":{"containers":[]}}}}"#;
+        let daemon_set: DaemonSet = serde_json::from_str(json).unwrap();
+        assert!(daemon_set.node_selector().is_none());
+    }
+
+    #[test]
+    fn deserialize_with_missing_number_available_field() {
+        let json = r#"{"status":{"desiredNumberScheduled":0}}"#;
+        let daemon_set: DaemonSet = serde_json::from_str(json).unwrap();
+        assert_eq!(daemon_set.status.unwrap().number_available, None);
+    }
+
+    #[test]
+    fn deserialize_with_missing_spec_and_status() {
+        let json = r#"{}"#;
+        let daemon_set: DaemonSet = serde_json::from_str(json).unwrap();
+        assert!(daemon_set.node_selector().is_none());
+        assert!(daemon_set.status.is_none());
+    }
+}
@@ -0,0 +1,39 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// The metadata common to every workload type; used to read annotations regardless of the workload's kind.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkloadMetadata {
+    pub metadata: Option<ObjectMeta>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectMeta {
+    pub annotations: Option<HashMap<String, String>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WorkloadMetadata;
+
+    #[test]
+    fn deserialize_with_annotations() {
+        let json =
+            r#"{"metadata":{"name":"app","annotations":{"backup-tools/scale":"false"}},"spec":{}}"#;
+        let workload: WorkloadMetadata = serde_json::from_str(json).unwrap();
+        let annotations = workload.metadata.unwrap().annotations.unwrap();
+        assert_eq!(
+            annotations.get("backup-tools/scale"),
+            Some(&String::from("false"))
+        );
+    }
+
+    #[test]
+    fn deserialize_with_missing_annotations() {
+        let json = r#"{"metadata":{"name":"app"}}"#;
+        let workload: WorkloadMetadata = serde_json::from_str(json).unwrap();
+        assert!(workload.metadata.unwrap().annotations.is_none());
+    }
+}
@@ -1,3 +1,9 @@
+mod cron_job;
+mod daemon_set;
 mod deployment;
+mod metadata;
 
+pub use cron_job::CronJob;
+pub use daemon_set::DaemonSet;
 pub use deployment::Deployment;
+pub use metadata::WorkloadMetadata;
@@ -1,12 +1,13 @@
+use crate::k8s::config::K8S_PREFIX;
 use crate::k8s::{DefaultK8sClient, K8sClient, K8sConfig};
 use anyhow::{anyhow, Context, Result};
 use envy::prefixed;
-use std::fs::read_to_string;
 use std::thread::sleep;
 use std::time::Duration;
-use tracing::{error, info, trace_span};
+use tracing::{error, info, trace_span, warn};
 
-const K8S_PREFIX: &str = "KUBERNETES_";
+const SCALE_TIMEOUT_SECS: u64 = 120;
+const DEFAULT_SUSPEND_TIMEOUT_SECS: u64 = 120;
 
 pub fn scale_deployment(inner: impl FnOnce() -> Result<()>) -> Result<()> {
     let span = trace_span!("k8s");
@@ -14,18 +15,29 @@ pub fn scale_deployment(inner: impl FnOnce() -> Result<()>) -> Result<()> {
 
     let k8s_config = prefixed(K8S_PREFIX).from_env::<K8sConfig>()?;
     let k8s_client = DefaultK8sClient::new(&k8s_config)?;
-    let service_namespace = k8s_config
-        .service_namespace
-        .clone()
-        .or_else(|| get_namespace(&k8s_config))
-        .ok_or_else(|| anyhow!("Failed to determine namespace."))?;
-
-    run_with_scaling(
-        &k8s_client,
-        &service_namespace,
-        &k8s_config.service_deployment_name,
-        inner,
-    )
+    let service_namespace = k8s_config.resolve_namespace()?;
+
+    if k8s_config.workload_type.is_scalable() {
+        run_with_scaling(
+            &k8s_client,
+            &service_namespace,
+            &k8s_config.service_deployment_name,
+            inner,
+        )
+    } else {
+        let timeout = Duration::from_secs(
+            k8s_config
+                .suspend_timeout
+                .unwrap_or(DEFAULT_SUSPEND_TIMEOUT_SECS),
+        );
+        run_with_suspension(
+            &k8s_client,
+            &service_namespace,
+            &k8s_config.service_deployment_name,
+            timeout,
+            inner,
+        )
+    }
 }
 
 fn run_with_scaling(
@@ -68,6 +80,69 @@ fn run_with_scaling(
     inner_result
 }
 
+fn run_with_suspension(
+    client: &impl K8sClient,
+    namespace: &str,
+    workload_name: &str,
+    timeout: Duration,
+    inner: impl FnOnce() -> Result<()>,
+) -> Result<()> {
+    let already_suspended = client
+        .is_suspended(namespace, workload_name)
+        .context("Retrieving original suspension state.")?;
+
+    if already_suspended {
+        info!("Workload already suspended, no suspension needed.")
+    } else {
+        info!("Suspending workload...");
+        if let Err(e) = suspend(namespace, workload_name, client, timeout) {
+            error!("Failed to suspend workload; attempting to resume it before exiting.");
+            client
+                .suspend(namespace, workload_name, false)
+                .unwrap_or_else(|resume_error| error!(ex=?resume_error, "Failed to resume workload."));
+            return Err(e.context("Failed to suspend workload."));
+        }
+        info!("Finished suspending workload.");
+    }
+
+    let inner_result = inner();
+    if inner_result.is_err() {
+        error!("Executing inner backup process failed! Attempting to resume workload anyway.");
+    }
+
+    if already_suspended {
+        info!("Workload was suspended initially so it will be left suspended.")
+    } else {
+        match client.suspend(namespace, workload_name, false) {
+            Ok(_) => info!("Resumed workload."),
+            Err(e) => error!(ex=?e, "Failed to resume workload."),
+        }
+    }
+
+    inner_result
+}
+
+/// Suspends the workload and waits for its running pods, such as the active Jobs of a CronJob, to finish. Suspending
+/// does not stop pods that are already running, so pods still running after the timeout are only warned about.
+fn suspend(
+    namespace: &str,
+    workload_name: &str,
+    client: &impl K8sClient,
+    timeout: Duration,
+) -> Result<()> {
+    client.suspend(namespace, workload_name, true)?;
+
+    info!(
+        timeout_seconds = timeout.as_secs(),
+        "Waiting for the workload's running pods to finish."
+    );
+    if let Err(e) = wait_for_replicas(namespace, workload_name, client, 0, timeout) {
+        warn!(ex=?e, "The workload still has running pods; continuing the backup while they finish.");
+    }
+
+    Ok(())
+}
+
 fn scale_down(namespace: &str, deployment_name: &str, client: &impl K8sClient) -> Result<i32> {
     scale(namespace, deployment_name, client, 0)
 }
@@ -95,14 +170,31 @@ fn scale(
     info!("Replica count prior to scale operation: {}", prev_replicas);
 
     info!(
-        "Beginning scale to target replica count of {}; waiting 120 seconds for the scaling to complete.",
-        &target_replicas
+        "Beginning scale to target replica count of {}; waiting {} seconds for the scaling to complete.",
+        &target_replicas,
+        SCALE_TIMEOUT_SECS
     );
     client.scale(namespace, deployment_name, target_replicas)?;
 
+    wait_for_replicas(
+        namespace,
+        deployment_name,
+        client,
+        target_replicas,
+        Duration::from_secs(SCALE_TIMEOUT_SECS),
+    )
+}
+
+fn wait_for_replicas(
+    namespace: &str,
+    deployment_name: &str,
+    client: &impl K8sClient,
+    target_replicas: i32,
+    timeout: Duration,
+) -> Result<i32> {
     let delay = Duration::from_secs(1);
     let mut replica_count = -1;
-    for i in 0..120 {
+    for i in 0..timeout.as_secs().max(1) {
         replica_count = client.get_available_replicas(namespace, deployment_name)?;
         if replica_count == target_replicas {
             return Ok(replica_count);
@@ -120,77 +212,26 @@ fn scale(
     }
 
     Err(anyhow!(format!(
-        "Failed to scale down application after 120 seconds; replica count: {}",
+        "Failed to reach target replica count of {} after {} seconds; replica count: {}",
+        target_replicas,
+        timeout.as_secs(),
         replica_count
     )))
 }
 
-fn get_namespace(config: &K8sConfig) -> Option<String> {
-    if let Some(path) = &config.namespace_file_path {
-        read_to_string(path).map_or_else(
-            |e| {
-                error!(ex=?e, "Failed to load namespace from namespace file.");
-                None
-            },
-            Some,
-        )
-    } else {
-        None
-    }
-}
-
 #[cfg(test)]
 mod tests {
-    use super::{run_with_scaling, scale};
-    use crate::k8s::K8sClient;
-    use anyhow::{anyhow, Result};
+    use super::{run_with_scaling, run_with_suspension, scale};
+    use crate::k8s::test_util::MockK8sClient;
+    use anyhow::anyhow;
     use std::cell::RefCell;
-    use std::collections::VecDeque;
-
-    struct MockK8sClient {
-        replica_responses: RefCell<VecDeque<i32>>,
-        scale_calls: RefCell<Vec<(String, String, i32)>>,
-    }
-
-    impl MockK8sClient {
-        fn new(replica_responses: Vec<i32>) -> Self {
-            MockK8sClient {
-                replica_responses: RefCell::new(replica_responses.into_iter().collect()),
-                scale_calls: RefCell::new(Vec::new()),
-            }
-        }
-
-        fn scale_call_count(&self) -> usize {
-            self.scale_calls.borrow().len()
-        }
-
-        fn scale_calls(&self) -> Vec<(String, String, i32)> {
-            self.scale_calls.borrow().clone()
-        }
-    }
-
-    impl K8sClient for MockK8sClient {
-        fn get_available_replicas(&self, _namespace: &str, _name: &str) -> Result<i32> {
-            Ok(self
-                .replica_responses
-                .borrow_mut()
-                .pop_front()
-                .expect("No more replica responses queued in MockK8sClient"))
-        }
-
-        fn scale(&self, namespace: &str, name: &str, count: i32) -> Result<()> {
-            self.scale_calls
-                .borrow_mut()
-                .push((namespace.to_string(), name.to_string(), count));
-            Ok(())
-        }
-    }
+    use std::time::Duration;
 
     // --- scale() ---
 
     #[test]
     fn scale_given_already_at_target_returns_count_without_calling_scale() {
-        let client = MockK8sClient::new(vec![0]);
+        let client = MockK8sClient::with_replicas(vec![0]);
 
         let result = scale("ns", "deploy", &client, 0);
 
@@ -202,7 +243,7 @@ mod tests {
     #[test]
     fn scale_given_target_reached_on_first_poll_returns_target() {
         // First get_available_replicas → 2 (current); after scale() call → 0 (target reached)
-        let client = MockK8sClient::new(vec![2, 0]);
+        let client = MockK8sClient::with_replicas(vec![2, 0]);
 
         let result = scale("ns", "deploy", &client, 0);
 
@@ -213,7 +254,7 @@ mod tests {
 
     #[test]
     fn scale_passes_correct_namespace_and_name_to_client() {
-        let client = MockK8sClient::new(vec![3, 0]);
+        let client = MockK8sClient::with_replicas(vec![3, 0]);
         scale("my-namespace", "my-deployment", &client, 0).unwrap();
         let calls = client.scale_calls();
         assert_eq!(calls.len(), 1);
@@ -227,7 +268,7 @@ mod tests {
 
     #[test]
     fn run_with_scaling_given_zero_replicas_skips_scale_and_runs_inner() {
-        let client = MockK8sClient::new(vec![0]);
+        let client = MockK8sClient::with_replicas(vec![0]);
         let inner_called = RefCell::new(false);
 
         let result = run_with_scaling(&client, "ns", "deploy", || {
@@ -252,7 +293,7 @@ mod tests {
         // 3. scale_down → scale(): first poll            → 0 (target reached)
         // 4. scale_up   → scale(): prev check            → 0 (not yet at target 2)
         // 5. scale_up   → scale(): first poll            → 2 (target reached)
-        let client = MockK8sClient::new(vec![2, 2, 0, 0, 2]);
+        let client = MockK8sClient::with_replicas(vec![2, 2, 0, 0, 2]);
         let inner_called = RefCell::new(false);
 
         let result = run_with_scaling(&client, "ns", "deploy", || {
@@ -271,7 +312,7 @@ mod tests {
     #[test]
     fn run_with_scaling_inner_failure_still_scales_up() {
         // Even when inner fails, scale-up must still be attempted
-        let client = MockK8sClient::new(vec![2, 2, 0, 0, 2]);
+        let client = MockK8sClient::with_replicas(vec![2, 2, 0, 0, 2]);
 
         let result = run_with_scaling(&client, "ns", "deploy", || {
             Err(anyhow!("backup failed"))
@@ -290,7 +331,7 @@ mod tests {
 
     #[test]
     fn run_with_scaling_propagates_inner_error_message() {
-        let client = MockK8sClient::new(vec![0]);
+        let client = MockK8sClient::with_replicas(vec![0]);
 
         let result = run_with_scaling(&client, "ns", "deploy", || {
             Err(anyhow!("specific inner error"))
@@ -302,4 +343,66 @@ mod tests {
             .to_string()
             .contains("specific inner error"));
     }
+
+    // --- run_with_suspension() ---
+
+    const TIMEOUT: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn run_with_suspension_given_pods_still_running_after_timeout_continues() {
+        let client = MockK8sClient::with_replicas(vec![1]);
+        let inner_called = RefCell::new(false);
+
+        let result = run_with_suspension(&client, "ns", "cronjob", TIMEOUT, || {
+            *inner_called.borrow_mut() = true;
+            Ok(())
+        });
+
+        assert!(result.is_ok());
+        assert!(*inner_called.borrow(), "Expected inner to be called");
+        assert_eq!(client.suspend_calls(), vec![true, false]);
+    }
+
+    #[test]
+    fn run_with_suspension_suspends_runs_inner_resumes() {
+        // The only replica query is the wait for running pods after suspending → 0
+        let client = MockK8sClient::with_replicas(vec![0]);
+        let inner_called = RefCell::new(false);
+
+        let result = run_with_suspension(&client, "ns", "cronjob", TIMEOUT, || {
+            *inner_called.borrow_mut() = true;
+            Ok(())
+        });
+
+        assert!(result.is_ok());
+        assert!(*inner_called.borrow(), "Expected inner to be called");
+        assert_eq!(client.suspend_calls(), vec![true, false]);
+        assert_eq!(client.scale_call_count(), 0);
+    }
+
+    #[test]
+    fn run_with_suspension_given_already_suspended_leaves_suspended() {
+        let client = MockK8sClient::suspended();
+
+        let result = run_with_suspension(&client, "ns", "cronjob", TIMEOUT, || Ok(()));
+
+        assert!(result.is_ok());
+        assert!(
+            client.suspend_calls().is_empty(),
+            "Expected no suspend calls when workload already suspended"
+        );
+    }
+
+    #[test]
+    fn run_with_suspension_inner_failure_still_resumes() {
+        // Running pods finish on the second poll.
+        let client = MockK8sClient::with_replicas(vec![1, 0]);
+
+        let result = run_with_suspension(&client, "ns", "daemonset", TIMEOUT, || {
+            Err(anyhow!("backup failed"))
+        });
+
+        assert!(result.is_err(), "Expected the inner error to be propagated");
+        assert_eq!(client.suspend_calls(), vec![true, false]);
+    }
 }
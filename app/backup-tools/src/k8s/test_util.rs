@@ -0,0 +1,148 @@
+use crate::k8s::model::access_review::ResourceAttributes;
+use crate::k8s::K8sClient;
+use anyhow::{bail, Result};
+use crossbeam::channel::Receiver;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+/// A `K8sClient` answering from queued responses and recording the changes it was asked to make.
+#[derive(Default)]
+pub struct MockK8sClient {
+    replica_responses: RefCell<VecDeque<i32>>,
+    scale_calls: RefCell<Vec<(String, String, i32)>>,
+    suspended: RefCell<bool>,
+    suspend_calls: RefCell<Vec<bool>>,
+    pods: Vec<String>,
+    exit_codes: RefCell<VecDeque<i32>>,
+    exec_calls: RefCell<Vec<(String, Vec<String>)>>,
+    denied_verbs: Vec<&'static str>,
+    reviews: RefCell<usize>,
+}
+
+impl MockK8sClient {
+    /// A client reporting the given available replica counts, one per query.
+    pub fn with_replicas(replica_responses: Vec<i32>) -> Self {
+        MockK8sClient {
+            replica_responses: RefCell::new(replica_responses.into_iter().collect()),
+            ..MockK8sClient::default()
+        }
+    }
+
+    /// A client whose workload is already suspended.
+    pub fn suspended() -> Self {
+        MockK8sClient {
+            suspended: RefCell::new(true),
+            ..MockK8sClient::default()
+        }
+    }
+
+    /// A client listing the given running pods, in which commands exit with the given codes in order.
+    pub fn with_pods(pods: Vec<&str>, exit_codes: Vec<i32>) -> Self {
+        MockK8sClient {
+            pods: pods.into_iter().map(String::from).collect(),
+            exit_codes: RefCell::new(exit_codes.into_iter().collect()),
+            ..MockK8sClient::default()
+        }
+    }
+
+    /// A client denying access reviews of the given verbs.
+    pub fn denying(denied_verbs: Vec<&'static str>) -> Self {
+        MockK8sClient {
+            denied_verbs,
+            ..MockK8sClient::default()
+        }
+    }
+
+    pub fn scale_calls(&self) -> Vec<(String, String, i32)> {
+        self.scale_calls.borrow().clone()
+    }
+
+    pub fn scale_call_count(&self) -> usize {
+        self.scale_calls.borrow().len()
+    }
+
+    pub fn suspend_calls(&self) -> Vec<bool> {
+        self.suspend_calls.borrow().clone()
+    }
+
+    /// The shell commands run in pods, in order.
+    pub fn exec_commands(&self) -> Vec<String> {
+        self.exec_calls
+            .borrow()
+            .iter()
+            .map(|(_, command)| command.last().unwrap().clone())
+            .collect()
+    }
+
+    pub fn review_count(&self) -> usize {
+        *self.reviews.borrow()
+    }
+}
+
+impl K8sClient for MockK8sClient {
+    fn get_available_replicas(&self, _namespace: &str, _name: &str) -> Result<i32> {
+        Ok(self
+            .replica_responses
+            .borrow_mut()
+            .pop_front()
+            .expect("No more replica responses queued in MockK8sClient"))
+    }
+
+    fn scale(&self, namespace: &str, name: &str, count: i32) -> Result<()> {
+        self.scale_calls
+            .borrow_mut()
+            .push((namespace.to_string(), name.to_string(), count));
+        Ok(())
+    }
+
+    fn is_suspended(&self, _namespace: &str, _name: &str) -> Result<bool> {
+        Ok(*self.suspended.borrow())
+    }
+
+    fn suspend(&self, _namespace: &str, _name: &str, suspended: bool) -> Result<()> {
+        *self.suspended.borrow_mut() = suspended;
+        self.suspend_calls.borrow_mut().push(suspended);
+        Ok(())
+    }
+
+    fn list_running_pods(&self, _namespace: &str, _label_selector: &str) -> Result<Vec<String>> {
+        Ok(self.pods.clone())
+    }
+
+    fn exec(
+        &self,
+        _namespace: &str,
+        pod: &str,
+        _container: Option<&str>,
+        command: &[&str],
+        _timeout: Duration,
+        shutdown_rx: &Receiver<()>,
+    ) -> Result<i32> {
+        if shutdown_rx.try_recv().is_ok() {
+            bail!("Killed command due to shutdown.");
+        }
+        self.exec_calls.borrow_mut().push((
+            pod.to_string(),
+            command.iter().map(|s| s.to_string()).collect(),
+        ));
+        Ok(self
+            .exit_codes
+            .borrow_mut()
+            .pop_front()
+            .expect("No more exit codes queued in MockK8sClient"))
+    }
+
+    fn is_allowed(&self, attributes: &ResourceAttributes) -> Result<bool> {
+        *self.reviews.borrow_mut() += 1;
+        Ok(!self.denied_verbs.contains(&attributes.verb.as_str()))
+    }
+
+    fn get_annotations(&self, _namespace: &str, _name: &str) -> Result<HashMap<String, String>> {
+        Ok(HashMap::new())
+    }
+
+    fn get_secret(&self, _namespace: &str, name: &str) -> Result<HashMap<String, String>> {
+        bail!("Secret {} not found.", name)
+    }
+}
@@ -1,10 +1,50 @@
 use serde::Deserialize;
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum WorkloadType {
     Deployment,
     StatefulSet,
+    ReplicaSet,
+    DaemonSet,
+    CronJob,
+}
+
+impl WorkloadType {
+    /// The group and version of the API serving this workload type, e.g. `apps/v1`.
+    pub fn api_group_version(&self) -> &'static str {
+        match self {
+            WorkloadType::CronJob => "batch/v1",
+            _ => "apps/v1",
+        }
+    }
+
+    /// The API group of this workload type, e.g. `apps`.
+    pub fn api_group(&self) -> &'static str {
+        self.api_group_version()
+            .split_once('/')
+            .map_or("", |(group, _)| group)
+    }
+
+    /// The plural resource name used in the API path for this workload type.
+    pub fn resource_name(&self) -> &'static str {
+        match self {
+            WorkloadType::Deployment => "deployments",
+            WorkloadType::StatefulSet => "statefulsets",
+            WorkloadType::ReplicaSet => "replicasets",
+            WorkloadType::DaemonSet => "daemonsets",
+            WorkloadType::CronJob => "cronjobs",
+        }
+    }
+
+    /// Whether the workload has a replica count that can be scaled to zero; workloads that cannot be scaled are
+    /// suspended instead.
+    pub fn is_scalable(&self) -> bool {
+        matches!(
+            self,
+            WorkloadType::Deployment | WorkloadType::StatefulSet | WorkloadType::ReplicaSet
+        )
+    }
 }
 
 #[cfg(test)]
@@ -31,10 +71,53 @@ mod tests {
         assert!(matches!(result.unwrap(), WorkloadType::StatefulSet));
     }
 
+    #[test]
+    fn deserialize_replicaset() {
+        let result = deserialize("REPLICASET");
+        assert!(result.is_ok());
+        assert!(matches!(result.unwrap(), WorkloadType::ReplicaSet));
+    }
+
+    #[test]
+    fn deserialize_daemonset() {
+        let result = deserialize("DAEMONSET");
+        assert!(result.is_ok());
+        assert!(matches!(result.unwrap(), WorkloadType::DaemonSet));
+    }
+
+    #[test]
+    fn deserialize_cronjob() {
+        let result = deserialize("CRONJOB");
+        assert!(result.is_ok());
+        assert!(matches!(result.unwrap(), WorkloadType::CronJob));
+    }
+
     #[test]
     fn deserialize_unknown_variant_returns_error() {
         assert!(deserialize("deployment").is_err());
         assert!(deserialize("StatefulSet").is_err());
+        assert!(deserialize("CRON_JOB").is_err());
         assert!(deserialize("").is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn is_scalable_only_for_replicated_workloads() {
+        assert!(WorkloadType::Deployment.is_scalable());
+        assert!(WorkloadType::StatefulSet.is_scalable());
+        assert!(WorkloadType::ReplicaSet.is_scalable());
+        assert!(!WorkloadType::DaemonSet.is_scalable());
+        assert!(!WorkloadType::CronJob.is_scalable());
+    }
+
+    #[test]
+    fn api_group_version_uses_batch_for_cronjob() {
+        assert_eq!(WorkloadType::CronJob.api_group_version(), "batch/v1");
+        assert_eq!(WorkloadType::DaemonSet.api_group_version(), "apps/v1");
+    }
+
+    #[test]
+    fn api_group_strips_version() {
+        assert_eq!(WorkloadType::CronJob.api_group(), "batch");
+        assert_eq!(WorkloadType::Deployment.api_group(), "apps");
+    }
+}
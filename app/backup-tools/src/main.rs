@@ -1,45 +1,92 @@
 use crate::app_config::AppConfig;
-use crate::db::backup_db;
-use crate::file::backup_files;
+use crate::common::redact::RedactingWriter;
+use crate::common::BackupPhase;
+use crate::pipeline::BackupPipeline;
 use anyhow::Result;
 use crossbeam::channel::{unbounded, Receiver};
 use envy::from_env;
 use rustls::crypto;
 use tracing::info;
+use tracing_subscriber::EnvFilter;
 
 mod app_config;
 mod common;
 mod db;
+mod docker;
 mod file;
 mod k8s;
+mod pipeline;
 
 fn main() -> Result<()> {
     dotenvy::dotenv().ok();
-    tracing_subscriber::fmt::init();
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_writer(RedactingWriter)
+        .init();
     crypto::aws_lc_rs::default_provider().install_default().expect("Failed to install rustls crypto provider.");
     
     info!("Beginning backup process...");
 
+    common::secret_files::apply_secret_files()?;
+    k8s::annotations::apply_annotation_config()?;
+    common::redact::register_env_secrets();
+
     let (tx, rx) = unbounded();
     ctrlc::set_handler(move || tx.send(()).expect("Failed to send signal on channel."))?;
 
     let app_config = from_env::<AppConfig>()?;
 
     let scale_deployment_enabled = app_config.scale_deployment_enabled.unwrap_or(false);
-    if scale_deployment_enabled {
-        k8s::scale::scale_deployment(|| run_backup(&app_config, &rx))?;
-    } else {
-        info!("Deployment scaling disabled, executing backup immediately.");
-        run_backup(&app_config, &rx)?;
+    let exec_hooks_enabled = app_config.exec_hooks_enabled.unwrap_or(false);
+    if scale_deployment_enabled || exec_hooks_enabled {
+        k8s::access::check_permissions(scale_deployment_enabled, exec_hooks_enabled)?;
     }
 
+    let pipeline = BackupPipeline::new(&app_config)?;
+    pipeline.clean_staging()?;
+    let run_phases = || -> Result<()> {
+        pipeline.run_phase(BackupPhase::Before, &rx)?;
+        if pipeline.has_steps_during_window() {
+            run_with_scaling(&app_config, &pipeline, &rx)?;
+        }
+        pipeline.run_phase(BackupPhase::After, &rx)
+    };
+
+    let result = if exec_hooks_enabled {
+        k8s::hooks::run_with_hooks(run_phases, &rx)
+    } else {
+        run_phases()
+    };
+    result.inspect_err(|_| pipeline.remove_incomplete_backup())?;
+
     info!("Backup completed!");
     Ok(())
 }
 
-fn run_backup(app_config: &AppConfig, shutdown_rx: &Receiver<()>) -> Result<()> {
-    backup_db(app_config, shutdown_rx)?;
-    backup_files(app_config, shutdown_rx)?;
+fn run_with_scaling(
+    app_config: &AppConfig,
+    pipeline: &BackupPipeline,
+    shutdown_rx: &Receiver<()>,
+) -> Result<()> {
+    let scale_deployment_enabled = app_config.scale_deployment_enabled.unwrap_or(false);
+    if scale_deployment_enabled {
+        k8s::scale::scale_deployment(|| run_with_docker_quiesce(app_config, pipeline, shutdown_rx))
+    } else {
+        info!("Deployment scaling disabled.");
+        run_with_docker_quiesce(app_config, pipeline, shutdown_rx)
+    }
+}
 
-    Ok(())
+fn run_with_docker_quiesce(
+    app_config: &AppConfig,
+    pipeline: &BackupPipeline,
+    shutdown_rx: &Receiver<()>,
+) -> Result<()> {
+    let docker_quiesce_enabled = app_config.docker_quiesce_enabled.unwrap_or(false);
+    if docker_quiesce_enabled {
+        docker::quiesce::quiesce_containers(|| pipeline.run_phase(BackupPhase::During, shutdown_rx))
+    } else {
+        info!("No workload quiescing enabled, executing backup immediately.");
+        pipeline.run_phase(BackupPhase::During, shutdown_rx)
+    }
 }
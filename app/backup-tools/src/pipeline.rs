@@ -0,0 +1,322 @@
+use crate::app_config::AppConfig;
+use crate::common::{BackupPhase, BackupType, DumpLocation};
+use crate::db::{backup_db, configured_backups, PhasedBackup};
+use crate::file::{backup_files, backup_name};
+use anyhow::{anyhow, bail, Context, Result};
+use crossbeam::channel::Receiver;
+use std::cell::RefCell;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use tracing::{error, info, info_span, warn};
+
+const DB_DIR: &str = "db";
+
+/// The database and file backups, each assigned to run before, during, or after the window in which the workload is
+/// scaled down.
+pub struct BackupPipeline<'a> {
+    app_config: &'a AppConfig,
+    db_backups: Vec<PhasedBackup<'a>>,
+    file_phase: BackupPhase,
+    /// The name of the backup made by this run within the destination path.
+    backup_name: PathBuf,
+    dump_location: DumpLocation,
+    staging_path: Option<PathBuf>,
+    /// Files excluded from the file backup by the database backups of earlier phases.
+    excludes: RefCell<Vec<PathBuf>>,
+}
+
+impl<'a> BackupPipeline<'a> {
+    pub fn new(app_config: &'a AppConfig) -> Result<BackupPipeline<'a>> {
+        let db_backups = configured_backups(app_config)?;
+        let file_phase = app_config.file_backup_phase.unwrap_or_default();
+
+        let dump_location = app_config.db_dump_location.unwrap_or_default();
+        let staging_path = match dump_location {
+            DumpLocation::Staging => {
+                let staging_path = app_config.db_staging_path.clone().ok_or_else(|| {
+                    anyhow!("DB_STAGING_PATH is required when staging database backups.")
+                })?;
+                // `..` or symbolic links must not hide that the staging path is within the source path.
+                if resolve_path(&staging_path).starts_with(resolve_path(&app_config.source_path)) {
+                    bail!("DB_STAGING_PATH must not be within the source path.");
+                }
+                Some(staging_path)
+            }
+            DumpLocation::Backup => {
+                if matches!(app_config.backup_type, Some(BackupType::Compressed)) {
+                    bail!("Database backups can only be written into incremental backups.");
+                }
+                None
+            }
+            DumpLocation::Source => None,
+        };
+
+        // Dumps must be finished before the file backup copies them.
+        if let Some(backup) = db_backups.iter().find(|b| b.phase > file_phase) {
+            bail!(
+                "The {} backup runs in the {:?} phase, after the file backup in the {:?} phase, and would not be \
+                included in the backup.",
+                backup.name(),
+                backup.phase,
+                file_phase
+            );
+        }
+
+        Ok(BackupPipeline {
+            app_config,
+            db_backups,
+            file_phase,
+            backup_name: backup_name(app_config),
+            dump_location,
+            staging_path,
+            excludes: RefCell::new(Vec::new()),
+        })
+    }
+
+    /// Removes the staged database backups: before the backup, so that a failed dump is never replaced by a stale one
+    /// from a previous run, and after the file backup copied them, so that the dumps do not stay on disk.
+    pub fn clean_staging(&self) -> Result<()> {
+        if let Some(staging_path) = &self.staging_path {
+            let db_path = staging_path.join(DB_DIR);
+            if db_path.exists() {
+                info!(path=%db_path.display(), "Removing staged database backups.");
+                fs::remove_dir_all(&db_path)
+                    .context("Error while removing staged database backups.")?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Removes the backup made by this run after a failure if database backups were written into it, so that later runs
+    /// do not mistake the partial backup for a complete one.
+    pub fn remove_incomplete_backup(&self) {
+        if self.dump_location != DumpLocation::Backup {
+            return;
+        }
+
+        let backup_path = self.app_config.destination_path.join(&self.backup_name);
+        if backup_path.exists() {
+            warn!(path=%backup_path.display(), "Removing incomplete backup.");
+            fs::remove_dir_all(&backup_path)
+                .unwrap_or_else(|e| error!(ex=?e, "Error while removing incomplete backup."));
+        }
+    }
+
+    /// Whether any backup runs while the workload is scaled down; if not, the workload is left running.
+    pub fn has_steps_during_window(&self) -> bool {
+        self.has_steps(BackupPhase::During)
+    }
+
+    pub fn run_phase(&self, phase: BackupPhase, shutdown_rx: &Receiver<()>) -> Result<()> {
+        if !self.has_steps(phase) {
+            return Ok(());
+        }
+
+        let span = info_span!("phase", phase = ?phase);
+        let _entered = span.enter();
+        info!("Beginning backup phase.");
+
+        let excludes = backup_db(
+            self.app_config,
+            &self.db_backups,
+            phase,
+            &self.db_path(),
+            shutdown_rx,
+        )?;
+        self.excludes.borrow_mut().extend(excludes);
+
+        if phase == self.file_phase {
+            // rsync would otherwise delete the database backups already written into the new backup.
+            if self.dump_location == DumpLocation::Backup {
+                self.excludes.borrow_mut().push(PathBuf::from(DB_DIR));
+            }
+
+            backup_files(
+                self.app_config,
+                &self.backup_name,
+                self.staging_path.as_deref(),
+                &self.excludes.borrow(),
+                shutdown_rx,
+            )?;
+            self.clean_staging()?;
+        }
+
+        Ok(())
+    }
+
+    fn db_path(&self) -> PathBuf {
+        match (self.dump_location, &self.staging_path) {
+            (DumpLocation::Staging, Some(staging_path)) => staging_path.join(DB_DIR),
+            (DumpLocation::Backup, _) => self
+                .app_config
+                .destination_path
+                .join(&self.backup_name)
+                .join(DB_DIR),
+            _ => self.app_config.source_path.join(DB_DIR),
+        }
+    }
+
+    fn has_steps(&self, phase: BackupPhase) -> bool {
+        self.file_phase == phase || self.db_backups.iter().any(|b| b.phase == phase)
+    }
+}
+
+/// Resolves `..` and symbolic links in a path, which may not have been created yet; the parts that do not exist cannot
+/// be symbolic links and are resolved lexically.
+fn resolve_path(path: &Path) -> PathBuf {
+    let mut resolved = PathBuf::new();
+    for component in path.components() {
+        let next = resolved.join(component);
+        match next.canonicalize() {
+            Ok(canonical) => resolved = canonical,
+            Err(_) if component == Component::ParentDir => {
+                resolved.pop();
+            }
+            Err(_) => resolved = next,
+        }
+    }
+
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BackupPipeline;
+    use crate::app_config::AppConfig;
+    use crate::common::{BackupPhase, BackupType, DumpLocation};
+    use std::env::temp_dir;
+    use std::fs;
+    use std::path::PathBuf;
+
+    #[test]
+    fn new_given_dump_after_file_backup_returns_error() {
+        let app_config = AppConfig {
+            postgres_backup_enabled: Some(true),
+            postgres_backup_phase: Some(BackupPhase::After),
+            ..AppConfig::default()
+        };
+        assert!(BackupPipeline::new(&app_config).is_err());
+
+        let app_config = AppConfig {
+            file_backup_phase: Some(BackupPhase::After),
+            ..app_config
+        };
+        assert!(BackupPipeline::new(&app_config).is_ok());
+    }
+
+    #[test]
+    fn has_steps_during_window_given_everything_before_returns_false() {
+        let app_config = AppConfig {
+            postgres_backup_enabled: Some(true),
+            db_backup_phase: Some(BackupPhase::Before),
+            ..AppConfig::default()
+        };
+        assert!(BackupPipeline::new(&app_config)
+            .unwrap()
+            .has_steps_during_window());
+
+        let app_config = AppConfig {
+            file_backup_phase: Some(BackupPhase::Before),
+            ..app_config
+        };
+        assert!(!BackupPipeline::new(&app_config)
+            .unwrap()
+            .has_steps_during_window());
+    }
+
+    #[test]
+    fn new_validates_dump_location() {
+        let app_config = AppConfig {
+            source_path: PathBuf::from("/source"),
+            db_dump_location: Some(DumpLocation::Staging),
+            ..AppConfig::default()
+        };
+        assert!(BackupPipeline::new(&app_config).is_err());
+
+        let app_config = AppConfig {
+            db_staging_path: Some(PathBuf::from("/source/staging")),
+            ..app_config
+        };
+        assert!(BackupPipeline::new(&app_config).is_err());
+
+        let app_config = AppConfig {
+            db_dump_location: Some(DumpLocation::Backup),
+            backup_type: Some(BackupType::Compressed),
+            ..AppConfig::default()
+        };
+        assert!(BackupPipeline::new(&app_config).is_err());
+    }
+
+    #[test]
+    fn new_given_staging_path_resolving_into_source_returns_error() {
+        let dir = temp_dir().join("backup_tools_staging_within_source");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("source")).unwrap();
+        std::os::unix::fs::symlink(dir.join("source"), dir.join("link")).unwrap();
+        let app_config = |staging_path: PathBuf| AppConfig {
+            source_path: dir.join("source"),
+            db_dump_location: Some(DumpLocation::Staging),
+            db_staging_path: Some(staging_path),
+            ..AppConfig::default()
+        };
+
+        let dot_dot = app_config(dir.join("other/../source/staging"));
+        let symlink = app_config(dir.join("link/staging"));
+        let outside = app_config(dir.join("staging"));
+        let results = [
+            BackupPipeline::new(&dot_dot).is_err(),
+            BackupPipeline::new(&symlink).is_err(),
+            BackupPipeline::new(&outside).is_ok(),
+        ];
+        fs::remove_dir_all(&dir).ok();
+        assert_eq!(results, [true, true, true]);
+    }
+
+    #[test]
+    fn db_path_given_dump_location_returns_db_directory() {
+        let app_config = |location: DumpLocation| AppConfig {
+            source_path: PathBuf::from("/source"),
+            destination_path: PathBuf::from("/destination"),
+            db_dump_location: Some(location),
+            db_staging_path: Some(PathBuf::from("/staging")),
+            ..AppConfig::default()
+        };
+
+        let source = app_config(DumpLocation::Source);
+        let pipeline = BackupPipeline::new(&source).unwrap();
+        assert_eq!(pipeline.db_path(), PathBuf::from("/source/db"));
+
+        let staging = app_config(DumpLocation::Staging);
+        let pipeline = BackupPipeline::new(&staging).unwrap();
+        assert_eq!(pipeline.db_path(), PathBuf::from("/staging/db"));
+
+        let backup = app_config(DumpLocation::Backup);
+        let pipeline = BackupPipeline::new(&backup).unwrap();
+        assert_eq!(
+            pipeline.db_path(),
+            PathBuf::from("/destination")
+                .join(&pipeline.backup_name)
+                .join("db")
+        );
+    }
+
+    #[test]
+    fn remove_incomplete_backup_given_dump_into_backup_removes_backup() {
+        let destination_path = temp_dir().join("backup_tools_incomplete_backup");
+        let _ = fs::remove_dir_all(&destination_path);
+        let app_config = AppConfig {
+            destination_path: destination_path.clone(),
+            db_dump_location: Some(DumpLocation::Backup),
+            ..AppConfig::default()
+        };
+        let pipeline = BackupPipeline::new(&app_config).unwrap();
+        fs::create_dir_all(pipeline.db_path()).unwrap();
+
+        pipeline.remove_incomplete_backup();
+
+        let removed = !destination_path.join(&pipeline.backup_name).exists();
+        fs::remove_dir_all(&destination_path).ok();
+        assert!(removed);
+    }
+}